@@ -54,8 +54,8 @@ fn main() {
         "(3 + (9 + 3 * 4 + (3 + 1) / 2 - 4)) * 2",
         "2^2^2^2", // This should be 65536 and not 256 because it is right associative.
     ];
-    for exp in expressions.into_iter() {
-        let mut parser = Rdp::new(StringInput::new(exp.clone()));
+    for exp in expressions.iter() {
+        let mut parser = Rdp::new(StringInput::new(exp));
         parser.expression();
         println!("{} = {}", exp, parser.compute());
     }
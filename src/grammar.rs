@@ -24,6 +24,45 @@
 /// `whitespace` and `comment` should be overridden and are void otherwise, while `any` and `eoi`
 /// are predefined rules.
 ///
+/// # Unicode classes
+///
+/// `[unicode(NAME)]` matches exactly one `char` from a Unicode general category or with a derived
+/// property. Since these classes are not rules, they do not clash with rules of the same name.
+/// When they fail, their `NAME` appears in
+/// [`Parser::expected_labels`](trait.Parser#tymethod.expected_labels).
+///
+/// | `NAME`             | Matches                                            |
+/// |--------------------|----------------------------------------------------|
+/// | `XID_START`        | a `char` that can start an identifier              |
+/// | `XID_CONTINUE`     | a `char` that can continue an identifier           |
+/// | `ALPHABETIC`       | a `char` with the `Alphabetic` property            |
+/// | `WHITE_SPACE`      | a `char` with the `White_Space` property           |
+/// | `LETTER`           | a `char` from the `Letter` category                |
+/// | `UPPERCASE_LETTER` | a `char` from the `Uppercase_Letter` category      |
+/// | `LOWERCASE_LETTER` | a `char` from the `Lowercase_Letter` category      |
+/// | `MARK`             | a `char` from the `Mark` category                  |
+/// | `DECIMAL_NUMBER`   | a `char` from the `Decimal_Number` category        |
+/// | `NUMBER`           | a `char` from the `Number` category                |
+/// | `PUNCTUATION`      | a `char` from the `Punctuation` category           |
+/// | `SYMBOL`           | a `char` from the `Symbol` category                |
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         ident = @{ ([unicode(XID_START)] | ["_"]) ~ [unicode(XID_CONTINUE)]* }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("λόγος_1"));
+///
+/// assert!(parser.ident());
+/// assert!(parser.end());
+/// # }
+/// ```
+///
 /// # Normal rules
 ///
 /// A normal rule will always create a [`Token`](struct.Token) and add it to the
@@ -70,6 +109,41 @@
 /// being matched in an atomic context. In other words, `a` and `b` will also be atomic when being
 /// matched inside of `ab`.
 ///
+/// # Keyword-aware rules `k@`
+///
+/// Keyword-aware rules work as atomic rules but fail when a reserved word matches at their start,
+/// which is useful for identifiers. Reserved words are defined by a `keyword` rule, while
+/// `[k"if"]` matches `"if"` only as a whole word, i.e. when it is not followed by the
+/// identifier-continuation class defined by an `ident_char` rule. Both rules need to be defined
+/// when used.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         statement = { [k"if"] ~ ident | ident }
+///         ident = k@{ ['a'..'z'] ~ ident_char* }
+///
+///         keyword = _{ [k"if"] | [k"else"] }
+///         ident_char = _{ ['a'..'z', '0'..'9', '_'] }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("if iffy"));
+///
+/// assert!(parser.statement());
+/// assert!(parser.end());
+///
+/// let mut parser = Rdp::new(StringInput::new("else"));
+///
+/// assert!(!parser.statement());
+/// # }
+/// ```
+///
 /// # Silent rules `_`
 ///
 /// Silent rules work like normal rules without appearing in
@@ -85,22 +159,214 @@
 ///
 /// # Syntax
 ///
-/// | Rule         | What it does                                                   |
-/// |--------------|----------------------------------------------------------------|
-/// | `["a"]`      | matches the exact string `"a"`                                 |
-/// | `[i"a"]`     | matches the exact string `"a"` case insensitively (ASCII only) |
-/// | `['a'..'z']` | matches one character between `'a'` and `'z'`                  |
-/// | `a`          | matches rule `a`                                               |
-/// | `a ~ b`      | matches the sequence `a` `b`                                   |
-/// | `a | b`      | matches either `a` or `b`                                      |
-/// | `a*`         | matches `a` zero or more times                                 |
-/// | `a+`         | matches `a` one or more times                                  |
-/// | `a?`         | optionally matches `a`                                         |
-/// | `&a`         | matches `a` without making progress                            |
-/// | `!a`         | matches if `a` doesn't match without making progress           |
-/// | `[push(a)]`  | matches a and pushes it's captured string down the stack       |
-/// | `[pop()]`    | pops a string from the stack and matches it                    |
-/// | `[peek()]`   | peeks a string from the stack and matches it                   |
+/// | Rule                 | What it does                                                        |
+/// |----------------------|---------------------------------------------------------------------|
+/// | `["a"]`              | matches the exact string `"a"`                                      |
+/// | `[i"a"]`             | matches the exact string `"a"` case insensitively (Unicode)         |
+/// | `[k"if"]`            | matches the exact string `"if"` if `ident_char` doesn't follow      |
+/// | `['a'..'z']`         | matches one character between `'a'` and `'z'`                       |
+/// | `['a'..'z', '_']`    | matches one character from a class of ranges and characters         |
+/// | `[^ '"', '\\']`      | matches one character outside of a class                            |
+/// | `[b"a"]`             | matches the exact bytes `b"a"`                                      |
+/// | `[b'a'..b'z']`       | matches one byte between `b'a'` and `b'z'`                          |
+/// | `[unicode(LETTER)]`  | matches one character from a [Unicode class](#unicode-classes)      |
+/// | `[kind(K::a)]`       | matches one element of kind `K::a` of a [`SliceInput`][slice-input] |
+/// | `a`                  | matches rule `a`                                                    |
+/// | `a ~ b`              | matches the sequence `a` `b`                                        |
+/// | `a | b`              | matches either `a` or `b`                                           |
+/// | `["a"] | ["ab"]`     | matches the longest of the literals, in any order                   |
+/// | `a*`                 | matches `a` zero or more times                                      |
+/// | `a+`                 | matches `a` one or more times                                       |
+/// | `a?`                 | optionally matches `a`                                              |
+/// | `a{n}`               | matches `a` exactly `n` times                                       |
+/// | `a{n,}`              | matches `a` at least `n` times                                      |
+/// | `a{,m}`              | matches `a` at most `m` times                                       |
+/// | `a{n,m}`             | matches `a` between `n` and `m` times                               |
+/// | `&a`                 | matches `a` without making progress                                 |
+/// | `!a`                 | matches if `a` doesn't match without making progress                |
+/// | `[push(a)]`          | matches a and pushes it's captured string down the stack            |
+/// | `[pop()]`            | pops a string from the stack and matches it                         |
+/// | `[peek()]`           | peeks a string from the stack and matches it                        |
+/// | `[drop()]`           | pops a string from the stack without matching it                    |
+/// | `[pop_all()]`        | pops all strings from the stack and matches them, top first         |
+/// | `[peek_all()]`       | matches all strings from the stack, top first                       |
+/// | `[pop("(" => ")")]`  | pops a string from the stack and matches what it maps to            |
+/// | `[peek("(" => ")")]` | peeks a string from the stack and matches what it maps to           |
+/// | `[cut()]`            | commits to the current alternative                                  |
+/// | `[recover(a, b)]`    | matches `a` or skips input until `b` matches                        |
+/// | `#[label("x")] a`    | matches `a`, which is expected as `"x"` if it fails                 |
+///
+/// [slice-input]: struct.SliceInput
+///
+/// `[pop()]`, `[peek()]` and `[drop()]` fail on an empty stack, which gets tracked as
+/// `Rule::pop`, `Rule::peek` and `Rule::drop` in
+/// [`Parser::expected`](trait.Parser#tymethod.expected). Changes to the stack are undone when
+/// backtracking.
+///
+/// ## Cut
+///
+/// `[cut()]` always matches, but once it has, failing to match the rest of the current alternative
+/// makes the whole parse fail instead of backtracking into other alternatives. The cut lasts until
+/// the end of the innermost rule, alternative, repetition, optional or lookahead it is part of.
+///
+/// After such a failure, [`Parser::cut_failed`](trait.Parser#tymethod.cut_failed) returns `true`
+/// and [`Parser::expected`](trait.Parser#tymethod.expected) only reports failures that happened
+/// after the cut. If none were tracked, the rule containing the cut is reported where the cut
+/// was committed.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         stat   = { ["function"] ~ [cut()] ~ name ~ ["()"] | name ~ ["()"] }
+///         name   = @{ ['a'..'z']+ }
+///         number = @{ ['0'..'9']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("function 1()"));
+///
+/// assert!(!parser.stat());
+/// assert!(parser.cut_failed());
+/// assert_eq!(parser.expected(), (vec![Rule::name], 9));
+/// # }
+/// ```
+///
+/// ## Error recovery
+///
+/// `[recover(a, b)]` matches rule `a` if it can. Otherwise, it skips input until rule `b` matches
+/// or the input ends, and queues a `Token` of `Rule::error` over the skipped input, so parsing
+/// goes on and a partial tree is always produced. If `b` matches right away, the `Token` is empty
+/// and `b` is left for the rest of the expression to match. It doesn't match at the end of the
+/// input, nor twice in a row at the same position, which ends repetitions. Failures after a
+/// [cut](#cut) inside `a` are recovered from as well.
+///
+/// Since an unexpected `b` is an error as well, repetitions should check for the input that ends
+/// them before recovering, like `!["end"]` below.
+///
+/// [`Parser::errors`](trait.Parser#tymethod.errors) returns the `Rule`s and
+/// [labels](#labels) that were expected at every recovered error.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         block     = { (!["end"] ~ [recover(statement, sync)] ~ [";"]?)* ~ ["end"] }
+///         statement = { name ~ ["="] ~ number }
+///         sync      = _{ [";"] | ["end"] }
+///         name      = @{ ['a'..'z']+ }
+///         number    = @{ ['0'..'9']+ }
+///
+///         whitespace = _{ [" "] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("a = 1; b = ; ; c = 2 end"));
+///
+/// assert!(parser.block());
+/// assert!(parser.end());
+///
+/// assert_eq!(parser.queue()[4], Token::new(Rule::error, 7, 11));
+/// assert_eq!(parser.queue()[5], Token::new(Rule::error, 13, 13));
+/// assert_eq!(parser.errors(), vec![
+///     (vec![Rule::number], vec![], 11),
+///     (vec![Rule::name], vec![], 13)
+/// ]);
+/// # }
+/// ```
+///
+/// ## Labels
+///
+/// Rules, including silent ones, and sub-expressions can be given a human-readable label with
+/// `#[label("...")]`. When a labeled rule or expression fails without getting further than where it
+/// started, its failures are replaced by the label, which is returned by
+/// [`Parser::expected_labels`](trait.Parser#tymethod.expected_labels) instead of the `Rule`s in
+/// [`Parser::expected`](trait.Parser#tymethod.expected). Failures further in are kept, since they
+/// are more precise.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         #[label("an index")]
+///         index  = { ["["] ~ number ~ #[label("a closing `]`")] ["]"] }
+///         #[label("a number")]
+///         number = _{ ['0'..'9']+ }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("x"));
+///
+/// assert!(!parser.index());
+/// assert_eq!(parser.expected_labels(), (vec!["an index"], 0));
+///
+/// let mut parser = Rdp::new(StringInput::new("[x]"));
+///
+/// assert!(!parser.index());
+/// assert_eq!(parser.expected_labels(), (vec!["a number"], 1));
+///
+/// let mut parser = Rdp::new(StringInput::new("[1)"));
+///
+/// assert!(!parser.index());
+/// assert_eq!(parser.expected_labels(), (vec!["a closing `]`"], 2));
+/// # }
+/// ```
+///
+/// ## Left recursion
+///
+/// Rules marked with `#[left_recursive]` can be left-recursive, either directly or through other
+/// rules. A left-recursive rule first matches without its recursive alternatives, and then matches
+/// again with the previous match standing in for the recursive call, for as long as it keeps
+/// matching further. The resulting `Token`s are nested left-associatively.
+///
+/// Every cycle of left-recursive rules needs at least one marked rule, which is where parsing can
+/// enter the cycle, so marking all of its rules is the safest choice. Unmarked rules skip the
+/// bookkeeping, which keeps frequently called rules like `whitespace` cheap.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         #[left_recursive]
+///         sum    = { sum ~ ["+"] ~ number | number }
+///         number = { ['0'..'9']+ }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("1+2+3"));
+///
+/// assert!(parser.sum());
+/// assert!(parser.end());
+///
+/// assert_eq!(parser.queue()[0], Token::new(Rule::sum, 0, 5));
+/// assert_eq!(parser.queue()[1], Token::new(Rule::sum, 0, 3));
+/// # }
+/// ```
+///
+/// ## Memoization
+///
+/// Rules marked with `#[memo]` remember their result at every position and atomicity, so matching
+/// them again after backtracking replays the stored `Token`s instead of parsing again. This avoids
+/// exponential backtracking in grammars where many alternatives start with the same rule.
+///
+/// ```ignore
+/// #[memo]
+/// prefixexp = { var | functioncall | ["("] ~ exp ~ [")"] }
+/// ```
+///
+/// [`Parser::set_packrat`](trait.Parser#tymethod.set_packrat) memoizes every rule instead,
+/// turning the parser into a packrat parser with linear running time. Silent rules and calls that
+/// use the stack are never memoized.
 ///
 /// ## Precedence climbing
 ///
@@ -203,33 +469,35 @@ macro_rules! grammar {
 
           {
               let mut primary = |slf: &mut Self| {
-                  let pos = slf.input().pos();
-                  let len = slf.queue().len();
+                  let snapshot = slf.snapshot();
 
+                  slf.__hold(snapshot.pos);
                   slf.skip();
 
                   let result = grammar!(@conv $atomic slf [ $( $primary )* ] [] []);
 
                   if !result {
-                      slf.input_mut().set_pos(pos);
-                      slf.queue_mut().truncate(len);
+                      slf.restore(snapshot);
                   }
 
+                  slf.__unhold();
+
                   result
               };
               let mut climb = |slf: &mut Self| {
-                  let pos = slf.input().pos();
-                  let len = slf.queue().len();
+                  let snapshot = slf.snapshot();
 
+                  slf.__hold(snapshot.pos);
                   slf.skip();
 
                   let result = grammar!(@conv_prec pos (0u8) $atomic slf [ $( $ts )* ] [] []);
 
                   if result.is_none() {
-                      slf.input_mut().set_pos(pos);
-                      slf.queue_mut().truncate(len);
+                      slf.restore(snapshot);
                   }
 
+                  slf.__unhold();
+
                   result
               };
 
@@ -246,10 +514,26 @@ macro_rules! grammar {
                   $slf.prec_climb(len, pos, 0, None, &mut primary, &mut climb);
               }
 
-              result
+              result && !$slf.cut_failed()
           }
     };
 
+    // handle alternations of literals
+    ( @conv $atomic:tt $slf:ident [ [ $first:literal ] $( | [ $rest:literal ] )+ ] [] [] ) => {
+        grammar!(@trie $slf $first $( , $rest )*)
+    };
+    ( @conv $atomic:tt $slf:ident [ ( [ $first:literal ] $( | [ $rest:literal ] )+ )
+      $( $tail:tt )* ] $ops:tt [ $( $output:tt )* ] ) => {
+        grammar!(@conv $atomic $slf [ $( $tail )* ] $ops
+                 [ $( $output )* (( grammar!(@trie $slf $first $( , $rest )*) )) ])
+    };
+
+    // handle labels
+    ( @conv $atomic:tt $slf:ident [ # [ label( $label:expr ) ] $head:tt $( $tail:tt )* ]
+      $ops:tt $output:tt ) => {
+        grammar!(@conv $atomic $slf [ $head [ _label $label ] $( $tail )* ] $ops $output)
+    };
+
     // handle parens
     ( @conv $atomic:tt $slf:ident [ ( $( $head:tt )* ) $( $tail:tt )* ] [ $( $optail:tt )* ]
       [ $( $output:tt )* ] ) => {
@@ -310,9 +594,19 @@ macro_rules! grammar {
 
     // match
     ( @mtc $slf:ident (( $exp:expr )) )             => (($exp));
-    ( @mtc $slf:ident [ $left:tt .. $right:tt ] )   => (grammar!(@mtc $slf [$left, $right]));
-    ( @mtc $slf:ident [ $left:expr, $right:expr ] ) => {
-        $slf.input_mut().match_range($left, $right)
+    ( @mtc $slf:ident [ $left:tt .. $right:tt ] )   => {
+        $crate::RangeLimit::match_input($left, $right, $slf.input_mut())
+    };
+    ( @mtc $slf:ident [ ^ $( $class:tt )* ] )       => {
+        $slf.input_mut().match_char_by(|c| !grammar!(@class c [ $( $class )* ] (false)))
+    };
+    ( @mtc $slf:ident [ $left:tt .. $right:tt , $( $class:tt )* ] ) => {
+        $slf.input_mut().match_char_by(|c| {
+            grammar!(@class c [ $left .. $right , $( $class )* ] (false))
+        })
+    };
+    ( @mtc $slf:ident [ $single:tt , $( $class:tt )* ] ) => {
+        $slf.input_mut().match_char_by(|c| grammar!(@class c [ $single , $( $class )* ] (false)))
     };
     ( @mtc $slf:ident [ push( $rule:ident ) ] )     => {
         {
@@ -321,31 +615,177 @@ macro_rules! grammar {
             let result = $slf.$rule();
 
             if result {
-                let slice = $slf.input().slice(start, $slf.input().pos());
+                let end = $slf.input().pos();
 
-                $slf.stack_mut().push(slice.to_owned());
+                $slf.stack_push((start, end));
             }
 
             result
         }
     };
-    ( @mtc $slf:ident [ pop() ] )     => {
+    ( @mtc $slf:ident [ pop() ] )                   => {
+        $slf.try(false, |$slf| {
+            match $slf.stack_pop() {
+                Some((start, end)) => $slf.input_mut().match_slice(start, end),
+                None => grammar!(@empty $slf pop)
+            }
+        })
+    };
+    ( @mtc $slf:ident [ pop( $( $from:expr => $to:expr ),+ ) ] ) => {
+        $slf.try(false, |$slf| {
+            match $slf.stack_pop() {
+                Some((start, end)) => grammar!(@map $slf start end $( $from => $to ),+),
+                None => grammar!(@empty $slf pop)
+            }
+        })
+    };
+    ( @mtc $slf:ident [ peek() ] )                  => {
+        match $slf.stack().last().cloned() {
+            Some((start, end)) => $slf.input_mut().match_slice(start, end),
+            None => grammar!(@empty $slf peek)
+        }
+    };
+    ( @mtc $slf:ident [ peek( $( $from:expr => $to:expr ),+ ) ] ) => {
+        match $slf.stack().last().cloned() {
+            Some((start, end)) => grammar!(@map $slf start end $( $from => $to ),+),
+            None => grammar!(@empty $slf peek)
+        }
+    };
+    ( @mtc $slf:ident [ drop() ] )                  => {
+        match $slf.stack_pop() {
+            Some(_) => true,
+            None => grammar!(@empty $slf drop)
+        }
+    };
+    ( @mtc $slf:ident [ pop_all() ] )               => {
+        $slf.try(false, |$slf| {
+            while let Some((start, end)) = $slf.stack_pop() {
+                if !$slf.input_mut().match_slice(start, end) {
+                    return false
+                }
+            }
+
+            true
+        })
+    };
+    ( @mtc $slf:ident [ peek_all() ] )              => {
+        $slf.try(false, |$slf| {
+            for i in (0..$slf.stack().len()).rev() {
+                let (start, end) = $slf.stack()[i];
+
+                if !$slf.input_mut().match_slice(start, end) {
+                    return false
+                }
+            }
+
+            true
+        })
+    };
+    ( @mtc $slf:ident [ cut() ] )                   => ($slf.__cut());
+    ( @mtc $slf:ident [ recover( $rule:ident , $until:ident ) ] ) => {
+        $slf.__recover(|$slf| $slf.$rule(), |$slf| $slf.$until())
+    };
+    ( @mtc $slf:ident [ unicode( $name:ident ) ] )  => {
+        $slf.__unicode(grammar!(@unicode $name), stringify!($name))
+    };
+    ( @mtc $slf:ident [ kind( $kind:expr ) ] )      => {
+        $crate::KindInput::match_kind($slf.input_mut(), &$kind)
+    };
+    ( @mtc $slf:ident [ $str:expr ] )               => {
+        $crate::Literal::match_input($str, $slf.input_mut())
+    };
+    ( @mtc $slf:ident [ i $str:expr ] )             => ($slf.input_mut().match_insensitive($str));
+    ( @mtc $slf:ident [ k $str:expr ] )             => {
+        $slf.try(false, |$slf| {
+            $crate::Literal::match_input($str, $slf.input_mut()) && {
+                let atomic = $slf.is_atomic();
+
+                $slf.set_atomic(true);
+
+                let continued = $slf.try(true, |$slf| $slf.ident_char());
+
+                $slf.set_atomic(atomic);
+
+                !continued
+            }
+        })
+    };
+    ( @mtc $slf:ident $rule:ident)                 => ($slf.$rule());
+
+    // Unicode class predicates
+    ( @unicode ALPHABETIC )       => ($crate::unicode::is_alphabetic);
+    ( @unicode DECIMAL_NUMBER )   => ($crate::unicode::is_decimal_number);
+    ( @unicode LETTER )           => ($crate::unicode::is_letter);
+    ( @unicode LOWERCASE_LETTER ) => ($crate::unicode::is_lowercase_letter);
+    ( @unicode MARK )             => ($crate::unicode::is_mark);
+    ( @unicode NUMBER )           => ($crate::unicode::is_number);
+    ( @unicode PUNCTUATION )      => ($crate::unicode::is_punctuation);
+    ( @unicode SYMBOL )           => ($crate::unicode::is_symbol);
+    ( @unicode UPPERCASE_LETTER ) => ($crate::unicode::is_uppercase_letter);
+    ( @unicode WHITE_SPACE )      => ($crate::unicode::is_white_space);
+    ( @unicode XID_CONTINUE )     => ($crate::unicode::is_xid_continue);
+    ( @unicode XID_START )        => ($crate::unicode::is_xid_start);
+
+    // longest match of literals
+    ( @trie $slf:ident $( $literal:expr ),* ) => {
+        {
+            static TRIE: ::std::sync::OnceLock<$crate::Trie> = ::std::sync::OnceLock::new();
+
+            let trie = TRIE.get_or_init(|| {
+                $crate::Trie::new(&[ $( ::std::convert::AsRef::<[u8]>::as_ref($literal) ),* ])
+            });
+
+            $slf.input_mut().match_trie(trie)
+        }
+    };
+
+    // stack operations
+    ( @empty $slf:ident $rule:ident ) => {
         {
-            let string = $slf.stack_mut().pop().expect("can't pop empty slots");
+            let pos = $slf.input().pos();
 
-            $slf.input_mut().match_string(&string)
+            $slf.track(Rule::$rule, pos);
+
+            false
         }
     };
-    ( @mtc $slf:ident [ peek() ] )     => {
+    ( @map $slf:ident $start:ident $end:ident $( $from:expr => $to:expr ),+ ) => {
         {
-            let string = $slf.stack().last().expect("can't peek empty slots").clone();
+            let pos = $slf.input().pos();
+            let mut mapped = None;
+
+            $(
+                if mapped.is_none() {
+                    $slf.input_mut().set_pos($start);
 
-            $slf.input_mut().match_string(&string)
+                    if $crate::Literal::match_input($from, $slf.input_mut()) &&
+                       $slf.input().pos() == $end {
+                        mapped = Some($to);
+                    }
+                }
+            )+
+
+            $slf.input_mut().set_pos(pos);
+
+            match mapped {
+                Some(to) => $crate::Literal::match_input(to, $slf.input_mut()),
+                None => false
+            }
         }
     };
-    ( @mtc $slf:ident [ $str:expr ] )               => ($slf.input_mut().match_string($str));
-    ( @mtc $slf:ident [ i $str:expr ] )             => ($slf.input_mut().match_insensitive($str));
-    ( @mtc $slf:ident $rule:ident)                 => ($slf.$rule());
+
+    // character classes
+    ( @class $c:ident [] ($cond:expr) ) => ($cond);
+    ( @class $c:ident [ $left:tt .. $right:tt ] ($cond:expr) ) => {
+        $cond || ($left..=$right).contains(&$c)
+    };
+    ( @class $c:ident [ $left:tt .. $right:tt , $( $tail:tt )* ] ($cond:expr) ) => {
+        grammar!(@class $c [ $( $tail )* ] ($cond || ($left..=$right).contains(&$c)))
+    };
+    ( @class $c:ident [ $single:tt ] ($cond:expr) ) => ($cond || $c == $single);
+    ( @class $c:ident [ $single:tt , $( $tail:tt )* ] ($cond:expr) ) => {
+        grammar!(@class $c [ $( $tail )* ] ($cond || $c == $single))
+    };
 
     // process postfix
     ( @process $_atomic:tt $_slf:ident [( $result:expr )] [] ) => ($result);
@@ -362,7 +802,9 @@ macro_rules! grammar {
 
                     let result = grammar!(@mtc $slf $b);
 
-                    if $slf.input().pos() == pos && !$slf.eoi_matched() {
+                    // empty error tokens of recovery are kept, along with the skip before them
+                    if $slf.input().pos() == pos && !$slf.eoi_matched() &&
+                       !$slf.queue()[original_len..].iter().any(|token| token.rule == Rule::error) {
                         $slf.input_mut().set_pos(original_pos);
                         $slf.queue_mut().truncate(original_len);
                     }
@@ -384,31 +826,33 @@ macro_rules! grammar {
     ( @process $atomic:tt $slf:ident [ $b:tt $a:tt $( $tail:tt )* ] [ | $( $optail:tt )* ] ) => {
         {
             grammar!(@process $atomic $slf [((
-                grammar!(@mtc $slf $a) || grammar!(@mtc $slf $b)
+                grammar!(@scope $slf $a) || grammar!(@scope $slf $b)
             )) $( $tail )* ] [ $( $optail )* ])
         }
     };
     ( @process false $slf:ident [ $a:tt $( $tail:tt )* ] [ * $( $optail:tt )* ] ) => {
         {
             grammar!(@process false $slf [(( {
-                let mut pos = $slf.input().pos();
-                let mut len = $slf.queue().len();
+                let mut snapshot = $slf.snapshot();
+
+                $slf.__hold(snapshot.pos);
 
                 loop {
-                    if !grammar!(@mtc $slf $a) {
-                        $slf.input_mut().set_pos(pos);
-                        $slf.queue_mut().truncate(len);
+                    if !grammar!(@scope $slf $a) {
+                        $slf.restore(snapshot);
 
                         break
                     }
 
-                    pos = $slf.input().pos();
-                    len = $slf.queue().len();
+                    snapshot = $slf.snapshot();
 
+                    $slf.__move_hold(snapshot.pos);
                     $slf.skip();
                 }
 
-                true
+                $slf.__unhold();
+
+                !$slf.cut_failed()
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
@@ -416,33 +860,38 @@ macro_rules! grammar {
         {
             grammar!(@process true $slf [(( {
                 loop {
-                    if !grammar!(@mtc $slf $a) {
+                    if !grammar!(@scope $slf $a) {
                         break
                     }
                 }
 
-                true
+                !$slf.cut_failed()
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
     ( @process false $slf:ident [ $a:tt $( $tail:tt )* ] [ + $( $optail:tt )* ] ) => {
         {
-            grammar!(@process false $slf [(( if grammar!(@mtc $slf $a) {
+            grammar!(@process false $slf [(( if grammar!(@scope $slf $a) {
                 loop {
-                    let pos = $slf.input().pos();
-                    let len = $slf.queue().len();
+                    let snapshot = $slf.snapshot();
 
+                    $slf.__hold(snapshot.pos);
                     $slf.skip();
 
-                    if !grammar!(@mtc $slf $a) {
-                        $slf.input_mut().set_pos(pos);
-                        $slf.queue_mut().truncate(len);
+                    let result = grammar!(@scope $slf $a);
 
+                    if !result {
+                        $slf.restore(snapshot);
+                    }
+
+                    $slf.__unhold();
+
+                    if !result {
                         break
                     }
                 }
 
-                true
+                !$slf.cut_failed()
             } else {
                 false
             } )) $( $tail )* ] [ $( $optail )* ])
@@ -450,14 +899,14 @@ macro_rules! grammar {
     };
     ( @process true $slf:ident [ $a:tt $( $tail:tt )* ] [ + $( $optail:tt )* ] ) => {
         {
-            grammar!(@process true $slf [(( if grammar!(@mtc $slf $a) {
+            grammar!(@process true $slf [(( if grammar!(@scope $slf $a) {
                 loop {
-                    if !grammar!(@mtc $slf $a) {
+                    if !grammar!(@scope $slf $a) {
                         break
                     }
                 }
 
-                true
+                !$slf.cut_failed()
             } else {
                 false
             } )) $( $tail )* ] [ $( $optail )* ])
@@ -466,26 +915,52 @@ macro_rules! grammar {
     ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ] [ ? $( $optail:tt )* ] ) => {
         {
             grammar!(@process $atomic $slf [(( {
-                grammar!(@mtc $slf $a);
+                let _ = grammar!(@scope $slf $a);
 
-                true
+                !$slf.cut_failed()
             } )) $( $tail )* ] [ $( $optail )* ])
         }
     };
+    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ]
+      [ { , $max:expr } $( $optail:tt )* ] ) => {
+        grammar!(@process $atomic $slf [(( grammar!(@rep $atomic $slf $a (0) (Some($max))) ))
+                 $( $tail )* ] [ $( $optail )* ])
+    };
+    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ]
+      [ { $min:expr } $( $optail:tt )* ] ) => {
+        grammar!(@process $atomic $slf [(( grammar!(@rep $atomic $slf $a ($min) (Some($min))) ))
+                 $( $tail )* ] [ $( $optail )* ])
+    };
+    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ]
+      [ { $min:expr , } $( $optail:tt )* ] ) => {
+        grammar!(@process $atomic $slf [(( grammar!(@rep $atomic $slf $a ($min) (None)) ))
+                 $( $tail )* ] [ $( $optail )* ])
+    };
+    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ]
+      [ { $min:expr , $max:expr } $( $optail:tt )* ] ) => {
+        grammar!(@process $atomic $slf [(( grammar!(@rep $atomic $slf $a ($min) (Some($max))) ))
+                 $( $tail )* ] [ $( $optail )* ])
+    };
     ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ] [ _pres $( $optail:tt )* ] ) => {
         {
             grammar!(@process $atomic $slf [(( $slf.try(true, |$slf| {
-                grammar!(@mtc $slf $a)
+                grammar!(@scope $slf $a)
             }) )) $( $tail )* ] [ $( $optail )* ])
         }
     };
     ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ] [ _abs $( $optail:tt )* ] ) => {
         {
             grammar!(@process $atomic $slf [(( $slf.try(true, |$slf| {
-                !grammar!(@mtc $slf $a)
-            }) )) $( $tail )* ] [ $( $optail )* ])
+                !grammar!(@scope $slf $a)
+            }) && !$slf.cut_failed() )) $( $tail )* ] [ $( $optail )* ])
         }
     };
+    ( @process $atomic:tt $slf:ident [ $a:tt $( $tail:tt )* ]
+      [ [ _label $label:expr ] $( $optail:tt )* ] ) => {
+        grammar!(@process $atomic $slf [(( $slf.__with_label(Some($label), |$slf| {
+            grammar!(@mtc $slf $a)
+        }) )) $( $tail )* ] [ $( $optail )* ])
+    };
     ( @process $_atomic:tt $slf:ident [] [ $single:tt ] ) => {
         grammar!(@mtc $slf $single)
     };
@@ -493,6 +968,76 @@ macro_rules! grammar {
         grammar!(@process $atomic $slf [ $head $( $optail )* ] [ $( $tail )* ])
     };
 
+    // operands that can fail without failing the whole expression start a new cut scope
+    ( @scope $slf:ident $a:tt ) => ($slf.__cut_scope(|$slf| grammar!(@mtc $slf $a)));
+
+    // bounded repetition
+    ( @rep false $slf:ident $a:tt ($min:expr) ($max:expr) ) => {
+        {
+            let min: usize = $min;
+            let max: Option<usize> = $max;
+
+            let start = $slf.snapshot();
+
+            let mut snapshot = start;
+            let mut count = 0;
+
+            $slf.__hold(start.pos);
+
+            while max.map_or(true, |max| count < max) {
+                if count > 0 {
+                    $slf.skip();
+                }
+
+                if !grammar!(@scope $slf $a) {
+                    $slf.restore(snapshot);
+
+                    break
+                }
+
+                snapshot = $slf.snapshot();
+                count += 1;
+
+                // once `min` is reached, only the last repetition can still be undone
+                if count >= min {
+                    $slf.__move_hold(snapshot.pos);
+                }
+            }
+
+            if count < min {
+                $slf.restore(start);
+            }
+
+            $slf.__unhold();
+
+            count >= min && !$slf.cut_failed()
+        }
+    };
+    ( @rep true $slf:ident $a:tt ($min:expr) ($max:expr) ) => {
+        {
+            let min: usize = $min;
+            let max: Option<usize> = $max;
+
+            let start = $slf.snapshot();
+
+            let mut count = 0;
+
+            $slf.__hold(start.pos);
+
+            while max.map_or(true, |max| count < max) && grammar!(@scope $slf $a) {
+                count += 1;
+            }
+
+            if count < min {
+                $slf.restore(start);
+            }
+
+            $slf.__unhold();
+
+            count >= min && !$slf.cut_failed()
+        }
+    };
+
     // whitespace and comment are always atomic
     ( @atomic whitespace $_atomic:tt $slf:ident $rules:tt ) => {
         grammar!(@conv true $slf $rules [] [])
@@ -510,16 +1055,18 @@ macro_rules! grammar {
         pub fn try<F>(&mut self, revert: bool, rule: F) -> bool
             where F: FnOnce(&mut Self) -> bool {
 
-            let pos = self.input().pos();
-            let len = self.queue().len();
+            let snapshot = self.snapshot();
+
+            self.__hold(snapshot.pos);
 
             let result = rule(self);
 
             if revert || !result {
-                self.input_mut().set_pos(pos);
-                self.queue_mut().truncate(len);
+                self.restore(snapshot);
             }
 
+            self.__unhold();
+
             result
         }
 
@@ -545,6 +1092,10 @@ macro_rules! grammar {
                     let queue_pos = self.queue().len();
 
                     if !primary(self) {
+                        if self.cut_failed() {
+                            return (None, last_right)
+                        }
+
                         let last_pos = self.queue().get(pos).unwrap().end;
 
                         self.input_mut().set_pos(last_pos);
@@ -581,7 +1132,7 @@ macro_rules! grammar {
 
                     if let Some(rule) = rule {
                         let token = Token {
-                            rule:  rule,
+                            rule,
                             start: left,
                             end:   right
                         };
@@ -597,144 +1148,243 @@ macro_rules! grammar {
         }
     };
 
+    // rule attributes
+    ( @memo ) => (false);
+    ( @memo [ memo ] $( $_tail:tt )* ) => (true);
+    ( @memo [ $( $_attr:tt )* ] $( $tail:tt )* ) => (grammar!(@memo $( $tail )*));
+    ( @label ) => (None);
+    ( @label [ label( $label:expr ) ] $( $_tail:tt )* ) => (Some($label));
+    ( @label [ $( $_attr:tt )* ] $( $tail:tt )* ) => (grammar!(@label $( $tail )*));
+    ( @left_recursive ) => (false);
+    ( @left_recursive [ left_recursive ] $( $_tail:tt )* ) => (true);
+    ( @left_recursive [ $( $_attr:tt )* ] $( $tail:tt )* ) => {
+        grammar!(@left_recursive $( $tail )*)
+    };
+
     // normal rule
-    ( $name:ident = { $( $ts:tt )* } $( $tail:tt )* ) => {
+    ( $( # [ $( $attr:tt )* ] )* $name:ident = { $( $ts:tt )* } $( $tail:tt )* ) => {
         #[allow(unused_parens, unused_variables)]
         #[inline]
         pub fn $name(&mut self) -> bool {
-            let slf = self;
+            let label = grammar!(@label $( [ $( $attr )* ] )*);
+            let memo = grammar!(@memo $( [ $( $attr )* ] )*);
+            let left_recursive = grammar!(@left_recursive $( [ $( $attr )* ] )*);
 
-            let pos = slf.input().pos();
-            let len = slf.queue().len();
-            let tracked_len_pos = slf.tracked_len_pos();
+            self.__with_label(label, |slf| slf.__memoize(Rule::$name, memo, |slf| {
+                slf.__recurse(stringify!($name), left_recursive, |slf| {
+                    let pos = slf.input().pos();
+                    let len = slf.queue().len();
+                    let tracked_len_pos = slf.tracked_len_pos();
 
-            let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
+                    let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
 
-            if result {
-                let new_pos = slf.input().pos();
+                    if result {
+                        let new_pos = slf.input().pos();
 
-                let token = Token {
-                    rule:  Rule::$name,
-                    start: pos,
-                    end:   new_pos
-                };
+                        let token = Token {
+                            rule:  Rule::$name,
+                            start: pos,
+                            end:   new_pos
+                        };
 
-                slf.queue_mut().insert(len, token);
-            } else {
-                slf.queue_mut().truncate(len);
+                        slf.queue_mut().insert(len, token);
+                    } else {
+                        slf.queue_mut().truncate(len);
 
-                if slf.tracked_len_pos() == tracked_len_pos {
-                    slf.track(Rule::$name, pos);
-                }
-            }
+                        let (tracked_len, fail_pos) = slf.tracked_len_pos();
 
-            result
+                        // a cut that failed without tracking anything is reported where it was
+                        // committed
+                        if slf.cut_failed() && tracked_len == 0 {
+                            slf.track(Rule::$name, fail_pos);
+                        } else if (tracked_len, fail_pos) == tracked_len_pos {
+                            slf.track(Rule::$name, pos);
+                        }
+                    }
+
+                    result
+                })
+            }))
         }
 
         grammar!($( $tail )*);
     };
 
     // atomic rule
-    ( $name:ident = @{ $( $ts:tt )* } $( $tail:tt )* ) => {
+    ( $( # [ $( $attr:tt )* ] )* $name:ident = @{ $( $ts:tt )* } $( $tail:tt )* ) => {
         #[allow(unused_parens, unused_variables)]
         #[inline]
         pub fn $name(&mut self) -> bool {
-            let slf = self;
+            let label = grammar!(@label $( [ $( $attr )* ] )*);
+            let memo = grammar!(@memo $( [ $( $attr )* ] )*);
+            let left_recursive = grammar!(@left_recursive $( [ $( $attr )* ] )*);
 
-            let pos = slf.input().pos();
-            let len = slf.queue().len();
+            self.__with_label(label, |slf| slf.__memoize(Rule::$name, memo, |slf| {
+                slf.__recurse(stringify!($name), left_recursive, |slf| {
+                    let pos = slf.input().pos();
+                    let len = slf.queue().len();
 
-            let toggled = slf.is_atomic();
+                    let toggled = slf.is_atomic();
 
-            if !toggled {
-                slf.set_atomic(true);
-            }
+                    if !toggled {
+                        slf.set_atomic(true);
+                    }
 
-            let result = grammar!(@conv true slf [ $( $ts )* ] [] []);
+                    let result = grammar!(@conv true slf [ $( $ts )* ] [] []);
 
-            if !toggled {
-                slf.set_atomic(false);
-            }
+                    if !toggled {
+                        slf.set_atomic(false);
+                    }
 
-            if result {
-                let new_pos = slf.input().pos();
+                    if result {
+                        let new_pos = slf.input().pos();
 
-                let token = Token {
-                    rule:  Rule::$name,
-                    start: pos,
-                    end:   new_pos
-                };
+                        let token = Token {
+                            rule:  Rule::$name,
+                            start: pos,
+                            end:   new_pos
+                        };
 
-                slf.queue_mut().insert(len, token);
-            } else {
-                slf.queue_mut().truncate(len);
+                        slf.queue_mut().insert(len, token);
+                    } else {
+                        slf.queue_mut().truncate(len);
 
-                slf.track(Rule::$name, pos);
-            }
+                        slf.track(Rule::$name, pos);
+                    }
 
-            result
+                    result
+                })
+            }))
+        }
+
+        grammar!($( $tail )*);
+    };
+
+    // keyword-aware rule
+    ( $( # [ $( $attr:tt )* ] )* $name:ident = k@{ $( $ts:tt )* } $( $tail:tt )* ) => {
+        #[allow(unused_parens, unused_variables)]
+        #[inline]
+        pub fn $name(&mut self) -> bool {
+            let label = grammar!(@label $( [ $( $attr )* ] )*);
+            let memo = grammar!(@memo $( [ $( $attr )* ] )*);
+            let left_recursive = grammar!(@left_recursive $( [ $( $attr )* ] )*);
+
+            self.__with_label(label, |slf| slf.__memoize(Rule::$name, memo, |slf| {
+                slf.__recurse(stringify!($name), left_recursive, |slf| {
+                    let pos = slf.input().pos();
+                    let len = slf.queue().len();
+
+                    let toggled = slf.is_atomic();
+
+                    if !toggled {
+                        slf.set_atomic(true);
+                    }
+
+                    let result = !slf.try(true, |slf| slf.keyword()) &&
+                                 grammar!(@conv true slf [ $( $ts )* ] [] []);
+
+                    if !toggled {
+                        slf.set_atomic(false);
+                    }
+
+                    if result {
+                        let new_pos = slf.input().pos();
+
+                        let token = Token {
+                            rule:  Rule::$name,
+                            start: pos,
+                            end:   new_pos
+                        };
+
+                        slf.queue_mut().insert(len, token);
+                    } else {
+                        slf.queue_mut().truncate(len);
+
+                        slf.track(Rule::$name, pos);
+                    }
+
+                    result
+                })
+            }))
         }
 
         grammar!($( $tail )*);
     };
 
     // non-atomic rule
-    ( $name:ident = !@{ $( $ts:tt )* } $( $tail:tt )* ) => {
+    ( $( # [ $( $attr:tt )* ] )* $name:ident = !@{ $( $ts:tt )* } $( $tail:tt )* ) => {
         #[allow(unused_parens, unused_variables)]
         #[inline]
         pub fn $name(&mut self) -> bool {
-            let slf = self;
+            let label = grammar!(@label $( [ $( $attr )* ] )*);
+            let memo = grammar!(@memo $( [ $( $attr )* ] )*);
+            let left_recursive = grammar!(@left_recursive $( [ $( $attr )* ] )*);
 
-            let pos = slf.input().pos();
-            let len = slf.queue().len();
-            let tracked_len_pos = slf.tracked_len_pos();
+            self.__with_label(label, |slf| slf.__memoize(Rule::$name, memo, |slf| {
+                slf.__recurse(stringify!($name), left_recursive, |slf| {
+                    let pos = slf.input().pos();
+                    let len = slf.queue().len();
+                    let tracked_len_pos = slf.tracked_len_pos();
 
-            let toggled = slf.is_atomic();
+                    let toggled = slf.is_atomic();
 
-            if toggled {
-                slf.set_atomic(false);
-            }
+                    if toggled {
+                        slf.set_atomic(false);
+                    }
 
-            let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
+                    let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
 
-            if result {
-                let new_pos = slf.input().pos();
+                    if result {
+                        let new_pos = slf.input().pos();
 
-                let token = Token {
-                    rule:  Rule::$name,
-                    start: pos,
-                    end:   new_pos
-                };
+                        let token = Token {
+                            rule:  Rule::$name,
+                            start: pos,
+                            end:   new_pos
+                        };
 
-                slf.queue_mut().insert(len, token);
-            } else {
-                slf.queue_mut().truncate(len);
+                        slf.queue_mut().insert(len, token);
+                    } else {
+                        slf.queue_mut().truncate(len);
 
-                if slf.tracked_len_pos() == tracked_len_pos {
-                    slf.track(Rule::$name, pos);
-                }
-            }
+                        let (tracked_len, fail_pos) = slf.tracked_len_pos();
 
-            if toggled {
-                slf.set_atomic(true);
-            }
+                        // a cut that failed without tracking anything is reported where it was
+                        // committed
+                        if slf.cut_failed() && tracked_len == 0 {
+                            slf.track(Rule::$name, fail_pos);
+                        } else if (tracked_len, fail_pos) == tracked_len_pos {
+                            slf.track(Rule::$name, pos);
+                        }
+                    }
 
-            result
+                    if toggled {
+                        slf.set_atomic(true);
+                    }
+
+                    result
+                })
+            }))
         }
 
         grammar!($( $tail )*);
     };
 
     // silent rule
-    ( $name:ident = _{ $( $ts:tt )* } $( $tail:tt )* ) => {
+    ( $( # [ $( $attr:tt )* ] )* $name:ident = _{ $( $ts:tt )* } $( $tail:tt )* ) => {
         #[allow(unused_parens, unused_variables)]
         #[inline]
         pub fn $name(&mut self) -> bool {
-            let slf = self;
+            let label = grammar!(@label $( [ $( $attr )* ] )*);
+            let left_recursive = grammar!(@left_recursive $( [ $( $attr )* ] )*);
 
-            let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
+            self.__with_label(label, |slf| {
+                slf.__recurse(stringify!($name), left_recursive, |slf| {
+                    let result = grammar!(@atomic $name false slf [ $( $ts )* ]);
 
-            result
+                    result
+                })
+            })
         }
 
         grammar!($( $tail )*);
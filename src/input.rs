@@ -5,22 +5,50 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+use super::Trie;
+
 /// A `trait` that defines an input for a `Parser`.
 pub trait Input<'a> {
+    /// The type returned by [`Input::slice`](trait.Input#tymethod.slice), e.g. `&'a str` for text
+    /// or `&'a [u8]` for raw bytes.
+    type Slice;
+
     /// Returns length of an `Input`.
     fn len(&self) -> usize;
 
     /// Returns whether an `Input` is empty.
     fn is_empty(&self) -> bool;
 
+    /// Returns the position at which an `Input` starts, which is where parsing starts and where
+    /// `soi` matches. This is 0 unless positions are shifted, like in an
+    /// [`EmbeddedInput`](struct.EmbeddedInput).
+    #[inline]
+    fn start_pos(&self) -> usize {
+        0
+    }
+
+    /// Returns whether an `Input` has reached its end at the current position.
+    fn at_end(&self) -> bool;
+
     /// Returns current position of an `Input`.
     fn pos(&self) -> usize;
 
     /// Set current position of an `Input`.
     fn set_pos(&mut self, pos: usize);
 
+    /// Whether an `Input` releases its text before the positions passed to
+    /// [`discard`](trait.Input#method.discard). Parsers only keep track of the positions they may
+    /// still rewind to for such inputs.
+    const DISCARDS: bool = false;
+
+    /// Tells an `Input` that positions before `pos` will not be visited anymore, so that the text
+    /// before them can be released. Parsers call this whenever they leave a backtracking point.
+    /// Does nothing by default.
+    #[inline]
+    fn discard(&mut self, _pos: usize) {}
+
     /// Slices an `Input`.
-    fn slice(&self, start: usize, end: usize) -> &'a str;
+    fn slice(&self, start: usize, end: usize) -> Self::Slice;
 
     /// Returns the line and column of a position for an `Input`.
     fn line_col(&self, pos: usize) -> (usize, usize);
@@ -29,11 +57,94 @@ pub trait Input<'a> {
     /// `string.len()` in case it did.
     fn match_string(&mut self, string: &str) -> bool;
 
-    /// Matches `string` to an `Input` case insensitively, returns whether it matched, and advances
-    /// the position with `string.len()` in case it did.
+    /// Matches `string` to an `Input` case insensitively, using simple Unicode case folding,
+    /// returns whether it matched, and advances the position past the matched `char`s in case it
+    /// did.
     fn match_insensitive(&mut self, string: &str) -> bool;
 
     /// Matches if an `Input`'s current `char` is between `left` and `right`, and advances the
-    /// position with one `char` in case it did.
+    /// position with one `char` in case it did. `left` and `right` can have different UTF-8
+    /// lengths.
     fn match_range(&mut self, left: char, right: char) -> bool;
+
+    /// Matches if an `Input`'s current `char` satisfies `predicate`, and advances the position with
+    /// one `char` in case it did.
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool;
+
+    /// Matches any single unit of an `Input`, i.e. one `char` for text or one byte for raw bytes,
+    /// returns whether it matched, and advances the position past it in case it did.
+    fn match_any(&mut self) -> bool;
+
+    /// Matches `bytes` to an `Input`, returns whether it matched, and advances the position with
+    /// `bytes.len()` in case it did.
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool;
+
+    /// Matches if an `Input`'s current byte is between `left` and `right`, and advances the
+    /// position with one byte in case it did.
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool;
+
+    /// Matches the already parsed part of an `Input` between `start` and `end` to the current
+    /// position, and advances the position with `end - start` in case it did. Used by the stack
+    /// rules of `grammar!`.
+    fn match_slice(&mut self, start: usize, end: usize) -> bool;
+
+    /// Matches the longest literal of `trie` to an `Input`, returns whether any matched, and
+    /// advances the position past it in case one did. By default, this tries the literals one by
+    /// one with [`match_bytes`](trait.Input#tymethod.match_bytes), longest first, while inputs
+    /// that can see their bytes walk the trie instead.
+    #[inline]
+    fn match_trie(&mut self, trie: &Trie) -> bool {
+        trie.literals().iter().any(|literal| self.match_bytes(literal))
+    }
+}
+
+/// A `trait` implemented by literals that can be matched in `grammar!`. `&str` literals are matched
+/// with [`Input::match_string`](trait.Input#tymethod.match_string), while byte string literals are
+/// matched with [`Input::match_bytes`](trait.Input#tymethod.match_bytes).
+pub trait Literal {
+    /// Matches a literal to `input`.
+    fn match_input<'a, I: Input<'a>>(&self, input: &mut I) -> bool;
+}
+
+impl Literal for str {
+    #[inline]
+    fn match_input<'a, I: Input<'a>>(&self, input: &mut I) -> bool {
+        input.match_string(self)
+    }
+}
+
+impl Literal for [u8] {
+    #[inline]
+    fn match_input<'a, I: Input<'a>>(&self, input: &mut I) -> bool {
+        input.match_bytes(self)
+    }
+}
+
+impl<const N: usize> Literal for [u8; N] {
+    #[inline]
+    fn match_input<'a, I: Input<'a>>(&self, input: &mut I) -> bool {
+        input.match_bytes(self)
+    }
+}
+
+/// A `trait` implemented by the limits of ranges that can be matched in `grammar!`. `char` ranges
+/// are matched with [`Input::match_range`](trait.Input#tymethod.match_range), while `u8` ranges
+/// are matched with [`Input::match_byte_range`](trait.Input#tymethod.match_byte_range).
+pub trait RangeLimit: Copy {
+    /// Matches the range between `left` and `right` to `input`.
+    fn match_input<'a, I: Input<'a>>(left: Self, right: Self, input: &mut I) -> bool;
+}
+
+impl RangeLimit for char {
+    #[inline]
+    fn match_input<'a, I: Input<'a>>(left: char, right: char, input: &mut I) -> bool {
+        input.match_range(left, right)
+    }
+}
+
+impl RangeLimit for u8 {
+    #[inline]
+    fn match_input<'a, I: Input<'a>>(left: u8, right: u8, input: &mut I) -> bool {
+        input.match_byte_range(left, right)
+    }
 }
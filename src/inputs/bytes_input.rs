@@ -0,0 +1,297 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::OnceLock;
+
+use super::super::{Input, Trie};
+use super::chars;
+use super::line_index::LineIndex;
+
+/// A `struct` useful for matching in-memory bytes that are not necessarily valid UTF-8, like
+/// binary protocols or files in legacy encodings. Its slices are `&[u8]`.
+///
+/// `char` ranges and strings are matched against the UTF-8 encoding of the input, while
+/// [`line_col`](trait.Input#tymethod.line_col) counts columns in bytes.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::BytesInput;
+/// let mut input = BytesInput::new(b"\x02ab\xff");
+///
+/// assert!(input.match_byte_range(0x00, 0x1f));
+/// assert!(input.match_string("ab"));
+/// assert!(input.match_bytes(b"\xff"));
+/// assert_eq!(input.slice(1, 3), b"ab");
+/// ```
+pub struct BytesInput<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    lines: OnceLock<LineIndex>
+}
+
+impl<'a> BytesInput<'a> {
+    /// Creates a new `BytesInput` from a `&[u8]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Input;
+    /// # use pest::BytesInput;
+    /// let input = BytesInput::new(b"asd");
+    ///
+    /// assert_eq!(input.len(), 3);
+    /// ```
+    pub fn new(bytes: &'a [u8]) -> BytesInput<'a> {
+        BytesInput {
+            bytes,
+            pos: 0,
+            lines: OnceLock::new()
+        }
+    }
+
+    /// Returns the position of a 1-based `line` and byte `col`, or `None` if the line does not
+    /// exist or is shorter than `col`.
+    pub fn pos_at(&self, line: usize, col: usize) -> Option<usize> {
+        let (start, end) = self.line_index().bounds(self.bytes.len(), line)?;
+
+        if col == 0 || start + col - 1 > end {
+            None
+        } else {
+            Some(start + col - 1)
+        }
+    }
+
+    /// Returns the bytes of a 1-based `line` without its line ending, or `None` if the line does
+    /// not exist.
+    pub fn line(&self, line: usize) -> Option<&'a [u8]> {
+        let (start, end) = self.line_index().bounds(self.bytes.len(), line)?;
+
+        Some(&self.bytes[start..end])
+    }
+
+    #[inline]
+    fn line_index(&self) -> &LineIndex {
+        self.lines.get_or_init(|| LineIndex::new(self.bytes))
+    }
+}
+
+impl<'a> Input<'a> for BytesInput<'a> {
+    type Slice = &'a [u8];
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &'a [u8] {
+        &self.bytes[start..end]
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        let (line, start) = self.line_index().line_start(self.bytes.len(), pos);
+
+        (line, pos - start + 1)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.match_bytes(string.as_bytes())
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, string: &str) -> bool {
+        if let Some(len) = chars::match_insensitive(&self.bytes[self.pos..], string) {
+            self.pos += len;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        if let Some((c, len)) = chars::first_char(&self.bytes[self.pos..]) {
+            let result = left <= c && c <= right;
+
+            if result {
+                self.pos += len;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        if let Some((c, len)) = chars::first_char(&self.bytes[self.pos..]) {
+            let result = predicate(c);
+
+            if result {
+                self.pos += len;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        if self.pos < self.bytes.len() {
+            self.pos += 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        let to = self.pos + bytes.len();
+
+        if to <= self.bytes.len() {
+            let result = &self.bytes[self.pos..to] == bytes;
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        if let Some(&byte) = self.bytes.get(self.pos) {
+            let result = left <= byte && byte <= right;
+
+            if result {
+                self.pos += 1;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        let to = self.pos + (end - start);
+
+        if to <= self.bytes.len() {
+            let result = self.bytes[start..end] == self.bytes[self.pos..to];
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_trie(&mut self, trie: &Trie) -> bool {
+        if let Some(len) = trie.matches(&self.bytes[self.pos..]).last() {
+            self.pos += len;
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::BytesInput;
+
+    #[test]
+    fn empty() {
+        let mut input = BytesInput::new(b"");
+
+        assert!(input.is_empty());
+        assert!(input.match_bytes(b""));
+        assert!(!input.match_bytes(b"a"));
+    }
+
+    #[test]
+    fn slice() {
+        let input = BytesInput::new(b"as\xffasdf");
+
+        assert_eq!(input.slice(1, 3), b"s\xff");
+    }
+
+    #[test]
+    fn line_col() {
+        let input = BytesInput::new(b"a\rb\nc\r\nd\xff\xfe");
+
+        assert_eq!(input.line_col(0), (1, 1));
+        assert_eq!(input.line_col(2), (2, 1));
+        assert_eq!(input.line_col(4), (3, 1));
+        assert_eq!(input.line_col(7), (4, 1));
+        assert_eq!(input.line_col(10), (4, 4));
+        assert_eq!(input.pos_at(4, 4), Some(10));
+        assert_eq!(input.pos_at(4, 5), None);
+        assert_eq!(input.line(4), Some(&b"d\xff\xfe"[..]));
+    }
+
+    #[test]
+    fn match_byte_range() {
+        let mut input = BytesInput::new(b"\x80\xff");
+
+        assert!(input.match_byte_range(0x80, 0x80));
+        assert!(!input.match_byte_range(0x00, 0xfe));
+        assert!(input.match_byte_range(0x00, 0xff));
+        assert!(!input.match_byte_range(0x00, 0xff));
+
+        assert_eq!(input.pos(), 2);
+    }
+
+    #[test]
+    fn match_text() {
+        let mut input = BytesInput::new("SeLeCt嗨".as_bytes());
+
+        assert!(input.match_insensitive("select"));
+        assert!(input.match_range('一', '龥'));
+        assert_eq!(input.pos(), 9);
+    }
+}
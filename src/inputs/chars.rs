@@ -0,0 +1,100 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::str;
+
+/// Decodes the first `char` of UTF-8 `bytes` and returns it with its length, or `None` if
+/// `bytes` does not start with a valid `char`.
+#[inline]
+pub fn first_char(bytes: &[u8]) -> Option<(char, usize)> {
+    let bytes = &bytes[..::std::cmp::min(bytes.len(), 4)];
+
+    let valid = match str::from_utf8(bytes) {
+        Ok(string) => string,
+        Err(error) => str::from_utf8(&bytes[..error.valid_up_to()]).unwrap()
+    };
+
+    valid.chars().next().map(|c| (c, c.len_utf8()))
+}
+
+/// Returns the simple case folding of `c`, which is the same for all `char`s that only differ in
+/// case, e.g. `'Σ'`, `'σ'`, and `'ς'`, or `'K'` and the Kelvin sign. Mappings that would turn one
+/// `char` into more, like `'ß'` into `"ss"`, are not applied.
+#[inline]
+pub fn fold(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+
+    let mut upper = c.to_uppercase();
+
+    if let (Some(upper), None) = (upper.next(), upper.next()) {
+        let mut lower = upper.to_lowercase();
+
+        if let (Some(lower), None) = (lower.next(), lower.next()) {
+            return lower;
+        }
+    }
+
+    let mut lower = c.to_lowercase();
+
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) => lower,
+        _ => c
+    }
+}
+
+/// Matches `string` case insensitively to the start of UTF-8 `bytes` and returns the length of
+/// the match in `bytes`, which may differ from `string.len()`.
+#[inline]
+pub fn match_insensitive(bytes: &[u8], string: &str) -> Option<usize> {
+    let mut len = 0;
+
+    for expected in string.chars() {
+        match first_char(&bytes[len..]) {
+            Some((c, c_len)) if c == expected || fold(c) == fold(expected) => len += c_len,
+            _ => return None
+        }
+    }
+
+    Some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_char, fold, match_insensitive};
+
+    #[test]
+    fn first() {
+        assert_eq!(first_char(b""), None);
+        assert_eq!(first_char(b"\xff"), None);
+        assert_eq!(first_char(b"a\xff"), Some(('a', 1)));
+        assert_eq!(first_char("𝄞a".as_bytes()), Some(('𝄞', 4)));
+        assert_eq!(first_char(&"嗨".as_bytes()[..2]), None);
+    }
+
+    #[test]
+    fn folding() {
+        assert_eq!(fold('A'), 'a');
+        assert_eq!(fold('Σ'), fold('ς'));
+        assert_eq!(fold('σ'), fold('ς'));
+        assert_eq!(fold('\u{212a}'), 'k');
+        assert_eq!(fold('ſ'), 's');
+        assert_eq!(fold('ẞ'), 'ß');
+        assert_eq!(fold('Ꭰ'), fold('ꭰ'));
+        assert_eq!(fold('İ'), 'İ');
+        assert_eq!(fold('嗨'), '嗨');
+    }
+
+    #[test]
+    fn insensitive() {
+        assert_eq!(match_insensitive("ΣΊΣΥΦΟΣ!".as_bytes(), "σίσυφος"), Some(14));
+        assert_eq!(match_insensitive("\u{212a}elvin".as_bytes(), "kelvin"), Some(8));
+        assert_eq!(match_insensitive(b"kelvi", "kelvin"), None);
+        assert_eq!(match_insensitive(b"ss", "\u{df}"), None);
+    }
+}
@@ -0,0 +1,396 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use super::super::Input;
+use super::chars;
+use super::line_index::{Columns, LineIndex};
+
+/// A `struct` useful for matching text that is split in multiple `&str` chunks, like the buffer
+/// of an editor held in a rope or a piece table, without flattening it first.
+///
+/// Matches work across chunk boundaries. Its slices are `Cow<str>`s that borrow from a chunk when
+/// they fit in one and only allocate when they straddle multiple chunks.
+///
+/// # Examples
+///
+/// ```
+/// # use std::borrow::Cow;
+/// # use pest::ChunkedInput;
+/// # use pest::Input;
+/// let mut input = ChunkedInput::new(vec!["fn ma", "in() {", "}"]);
+///
+/// assert!(input.match_string("fn main"));
+/// assert!(input.match_range('(', ')'));
+///
+/// assert_eq!(input.slice(3, 5), Cow::Borrowed("ma"));
+/// assert_eq!(input.slice(3, 7), Cow::<str>::Owned(String::from("main")));
+/// ```
+pub struct ChunkedInput<'a> {
+    chunks: Vec<&'a str>,
+    starts: Vec<usize>,
+    len: usize,
+    pos: usize,
+    lines: OnceLock<LineIndex>,
+    columns: Columns
+}
+
+impl<'a> ChunkedInput<'a> {
+    /// Creates a new `ChunkedInput` from `&str` chunks in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::ChunkedInput;
+    /// # use pest::Input;
+    /// let input = ChunkedInput::new(vec!["as", "", "d"]);
+    ///
+    /// assert_eq!(input.len(), 3);
+    /// ```
+    pub fn new<I: IntoIterator<Item = &'a str>>(chunks: I) -> ChunkedInput<'a> {
+        let chunks: Vec<_> = chunks.into_iter().filter(|chunk| !chunk.is_empty()).collect();
+        let mut starts = Vec::with_capacity(chunks.len());
+        let mut len = 0;
+
+        for chunk in &chunks {
+            starts.push(len);
+            len += chunk.len();
+        }
+
+        ChunkedInput {
+            chunks,
+            starts,
+            len,
+            pos: 0,
+            lines: OnceLock::new(),
+            columns: Columns::Chars
+        }
+    }
+
+    /// Sets the unit in which [`line_col`](trait.Input#tymethod.line_col) counts columns. Defaults
+    /// to `Columns::Chars`.
+    pub fn set_columns(&mut self, columns: Columns) {
+        self.columns = columns;
+    }
+
+    /// Returns the text of a 1-based `line` without its line ending, or `None` if the line does
+    /// not exist.
+    pub fn line(&self, line: usize) -> Option<Cow<'a, str>> {
+        let (start, end) = self.line_index().bounds(self.len, line)?;
+
+        Some(self.slice(start, end))
+    }
+
+    #[inline]
+    fn line_index(&self) -> &LineIndex {
+        self.lines.get_or_init(|| {
+            LineIndex::from_bytes(self.chunks.iter().flat_map(|chunk| chunk.bytes()))
+        })
+    }
+
+    // Returns the index of the chunk containing `pos` and the offset of `pos` inside of it. The end
+    // of the input is at the end of the last chunk.
+    #[inline]
+    fn locate(&self, pos: usize) -> (usize, usize) {
+        if pos > self.len {
+            panic!("position out of bounds");
+        }
+
+        match self.starts.binary_search(&pos) {
+            Ok(i) => (i, 0),
+            Err(0) => (0, 0),
+            Err(i) => (i - 1, pos - self.starts[i - 1])
+        }
+    }
+
+    // Returns the pieces of text from `pos` until the end.
+    #[inline]
+    fn rest(&self, pos: usize) -> impl Iterator<Item = &'a str> + '_ {
+        let (i, offset) = self.locate(pos);
+
+        self.chunks.get(i).map(|chunk| &chunk[offset..]).into_iter()
+            .chain(self.chunks.iter().skip(i + 1).cloned())
+    }
+
+    #[inline]
+    fn starts_with(&self, pos: usize, mut bytes: &[u8]) -> bool {
+        for piece in self.rest(pos) {
+            let piece = piece.as_bytes();
+
+            if piece.len() >= bytes.len() {
+                return piece.starts_with(bytes);
+            }
+
+            if !bytes.starts_with(piece) {
+                return false;
+            }
+
+            bytes = &bytes[piece.len()..];
+        }
+
+        bytes.is_empty()
+    }
+
+    #[inline]
+    fn is_char_boundary(&self, pos: usize) -> bool {
+        let (i, offset) = self.locate(pos);
+
+        self.chunks.get(i).is_none_or(|chunk| chunk.is_char_boundary(offset))
+    }
+
+    #[inline]
+    fn current_char(&self) -> Option<char> {
+        // chunks are &strs, so chars never straddle them
+        self.rest(self.pos).next().and_then(|piece| piece.chars().next())
+    }
+}
+
+impl<'a> Input<'a> for ChunkedInput<'a> {
+    type Slice = Cow<'a, str>;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.pos == self.len
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> Cow<'a, str> {
+        if start > end {
+            panic!("slice starts after its end");
+        }
+
+        let (i, offset) = self.locate(start);
+
+        match self.chunks.get(i) {
+            Some(chunk) if offset + end - start <= chunk.len() => {
+                Cow::Borrowed(&chunk[offset..offset + end - start])
+            },
+            Some(_) => {
+                let (j, end_offset) = self.locate(end);
+                let mut string = String::with_capacity(end - start);
+
+                string.push_str(&self.chunks[i][offset..]);
+
+                for chunk in &self.chunks[i + 1..j] {
+                    string.push_str(chunk);
+                }
+
+                string.push_str(&self.chunks[j][..end_offset]);
+
+                Cow::Owned(string)
+            },
+            None => Cow::Borrowed("")
+        }
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        let (line, start) = self.line_index().line_start(self.len, pos);
+
+        (line, self.columns.count(&self.slice(start, pos)) + 1)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        let to = self.pos + string.len();
+
+        if to <= self.len && self.starts_with(self.pos, string.as_bytes()) {
+            self.pos = to;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, string: &str) -> bool {
+        let len = {
+            let mut chars = self.rest(self.pos).flat_map(|piece| piece.chars());
+            let mut len = 0;
+
+            for expected in string.chars() {
+                match chars.next() {
+                    Some(c) if c == expected || chars::fold(c) == chars::fold(expected) => {
+                        len += c.len_utf8()
+                    },
+                    _ => return false
+                }
+            }
+
+            len
+        };
+
+        self.pos += len;
+
+        true
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.match_char_by(|c| c >= left && c <= right)
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        match self.current_char() {
+            Some(c) if predicate(c) => {
+                self.pos += c.len_utf8();
+
+                true
+            },
+            _ => false
+        }
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        self.match_char_by(|_| true)
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        let to = self.pos + bytes.len();
+
+        if to <= self.len && self.is_char_boundary(to) && self.starts_with(self.pos, bytes) {
+            self.pos = to;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        let byte = self.rest(self.pos).next().and_then(|piece| piece.bytes().next());
+
+        match byte {
+            Some(byte) if byte >= left && byte <= right && self.is_char_boundary(self.pos + 1) => {
+                self.pos += 1;
+
+                true
+            },
+            _ => false
+        }
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        let slice = self.slice(start, end);
+
+        self.match_string(&slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::super::super::Input;
+    use super::ChunkedInput;
+
+    #[test]
+    fn straddling() {
+        let mut input = ChunkedInput::new(vec!["ab", "", "c", "嗨d"]);
+
+        assert!(input.match_string("abc嗨"));
+        assert!(!input.match_string("de"));
+        assert!(input.match_bytes(b"d"));
+        assert!(input.at_end());
+        assert!(!input.match_any());
+
+        input.set_pos(1);
+
+        assert!(input.match_insensitive("BC"));
+        assert!(input.match_range('一', '龥'));
+        assert!(!input.match_byte_range(b'a', b'c'));
+        assert!(input.match_byte_range(b'a', b'd'));
+    }
+
+    #[test]
+    fn char_boundaries() {
+        let mut input = ChunkedInput::new(vec!["é", "x"]);
+
+        assert!(!input.match_byte_range(0, 255));
+        assert!(!input.match_bytes(b"\xc3"));
+        assert_eq!(input.pos(), 0);
+        assert!(input.match_any());
+        assert!(input.match_byte_range(b'x', b'x'));
+        assert!(input.at_end());
+
+        input.set_pos(0);
+
+        assert!(input.match_bytes("é".as_bytes()));
+        assert!(input.match_bytes(b"x"));
+    }
+
+    #[test]
+    fn slices() {
+        let input = ChunkedInput::new(vec!["ab", "c", "de"]);
+
+        assert_eq!(input.slice(0, 2), Cow::Borrowed("ab"));
+        assert_eq!(input.slice(2, 2), Cow::Borrowed(""));
+        assert_eq!(input.slice(3, 5), Cow::Borrowed("de"));
+        assert_eq!(input.slice(5, 5), Cow::Borrowed(""));
+        assert_eq!(input.slice(1, 4), Cow::<str>::Owned("bcd".to_owned()));
+        assert_eq!(input.slice(0, 5), Cow::<str>::Owned("abcde".to_owned()));
+    }
+
+    #[test]
+    fn match_slice() {
+        let mut input = ChunkedInput::new(vec!["a", "ba", "b"]);
+
+        input.set_pos(2);
+
+        assert!(input.match_slice(0, 2));
+        assert!(input.at_end());
+    }
+
+    #[test]
+    fn lines() {
+        let input = ChunkedInput::new(vec!["ab\r", "\ncd", "\n"]);
+
+        assert_eq!(input.line_col(4), (2, 1));
+        assert_eq!(input.line_col(6), (2, 3));
+        assert_eq!(input.line_col(7), (3, 1));
+        assert_eq!(input.line(2), Some(Cow::Borrowed("cd")));
+    }
+
+    #[test]
+    fn empty() {
+        let mut input = ChunkedInput::new(vec![]);
+
+        assert!(input.is_empty());
+        assert!(input.match_string(""));
+        assert!(!input.match_any());
+        assert_eq!(input.slice(0, 0), "");
+        assert_eq!(input.line_col(0), (1, 1));
+    }
+}
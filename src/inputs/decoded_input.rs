@@ -0,0 +1,425 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::char;
+use std::str;
+use std::sync::OnceLock;
+
+use super::super::{Input, Trie};
+use super::line_index::{Columns, LineIndex};
+use super::{OwnedInput, SharedStr};
+
+// Windows-1252 0x80..0xa0; bytes without a character map to the C1 controls, like in browsers
+const WINDOWS_1252: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}'
+];
+
+/// An `enum` of the encodings that a [`DecodedInput`](struct.DecodedInput) can decode.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Encoding {
+    /// UTF-8, with or without a byte order mark
+    Utf8,
+    /// little-endian UTF-16, with or without a byte order mark
+    Utf16Le,
+    /// big-endian UTF-16, with or without a byte order mark
+    Utf16Be,
+    /// Windows-1252, a superset of Latin-1 used by legacy Windows programs
+    Windows1252
+}
+
+impl Encoding {
+    /// Detects the encoding of `bytes` from its byte order mark. Without one, `bytes` is UTF-8 if
+    /// it's valid UTF-8 and Windows-1252 otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Encoding;
+    /// assert_eq!(Encoding::detect(b"\xff\xfea\x00"), Encoding::Utf16Le);
+    /// assert_eq!(Encoding::detect("café".as_bytes()), Encoding::Utf8);
+    /// assert_eq!(Encoding::detect(b"caf\xe9"), Encoding::Windows1252);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        if bytes.starts_with(b"\xef\xbb\xbf") {
+            Encoding::Utf8
+        } else if bytes.starts_with(b"\xff\xfe") {
+            Encoding::Utf16Le
+        } else if bytes.starts_with(b"\xfe\xff") {
+            Encoding::Utf16Be
+        } else if str::from_utf8(bytes).is_ok() {
+            Encoding::Utf8
+        } else {
+            Encoding::Windows1252
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => b"\xef\xbb\xbf",
+            Encoding::Utf16Le => b"\xff\xfe",
+            Encoding::Utf16Be => b"\xfe\xff",
+            Encoding::Windows1252 => b""
+        }
+    }
+}
+
+// A run of chars that all have the same length in the decoded and in the original text.
+struct Run {
+    start: usize,
+    original: usize,
+    len: usize,
+    original_len: usize
+}
+
+struct Decoder {
+    text: String,
+    runs: Vec<Run>,
+    original: usize
+}
+
+impl Decoder {
+    fn push(&mut self, c: char, original_len: usize) {
+        let len = c.len_utf8();
+        let extends = match self.runs.last() {
+            Some(run) => run.len == len && run.original_len == original_len,
+            None => false
+        };
+
+        if !extends {
+            self.runs.push(Run {
+                start: self.text.len(),
+                original: self.original,
+                len,
+                original_len
+            });
+        }
+
+        self.text.push(c);
+        self.original += original_len;
+    }
+}
+
+/// A `struct` that decodes bytes in an [`Encoding`](enum.Encoding) other than UTF-8, like
+/// UTF-16 or Windows-1252, into an internal UTF-8 buffer that the grammar matches. Its slices are
+/// [`SharedStr`](struct.SharedStr)s of the decoded text.
+///
+/// Positions, and thus tokens and [`expected`](trait.Parser#tymethod.expected) positions, are
+/// positions in the decoded text, which [`original_pos`](#method.original_pos) translates back to
+/// byte offsets in the original bytes. Lines and columns are the same in both texts, except for
+/// `Columns::Bytes`, which counts the original bytes. A byte order mark is skipped, while
+/// malformed input is decoded as `'\u{fffd}'`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::DecodedInput;
+/// # use pest::Encoding;
+/// # use pest::Input;
+/// let mut input = DecodedInput::new(b"\xff\xfea\x00\n\x00\xe9\x00");
+///
+/// assert_eq!(input.encoding(), Encoding::Utf16Le);
+/// assert!(input.match_string("a\n"));
+/// assert_eq!(input.slice(2, 4), "é");
+/// assert_eq!(input.original_pos(2), 6);
+/// assert_eq!(input.line_col(4), (2, 2));
+/// ```
+pub struct DecodedInput {
+    input: OwnedInput,
+    encoding: Encoding,
+    runs: Vec<Run>,
+    original_len: usize,
+    lines: OnceLock<LineIndex>,
+    columns: Columns
+}
+
+impl DecodedInput {
+    /// Creates a new `DecodedInput` from `bytes` in the [detected](enum.Encoding#method.detect)
+    /// encoding.
+    pub fn new(bytes: &[u8]) -> DecodedInput {
+        DecodedInput::with_encoding(bytes, Encoding::detect(bytes))
+    }
+
+    /// Creates a new `DecodedInput` from `bytes` in `encoding`. A byte order mark of the
+    /// encoding is skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::DecodedInput;
+    /// # use pest::Encoding;
+    /// let input = DecodedInput::with_encoding(b"\x00\xe9\xd8\x34\xdd\x1e", Encoding::Utf16Be);
+    ///
+    /// assert_eq!(input.as_str(), "é𝄞");
+    /// assert_eq!(input.original_pos(6), 6);
+    /// ```
+    pub fn with_encoding(bytes: &[u8], encoding: Encoding) -> DecodedInput {
+        let bom = if bytes.starts_with(encoding.bom()) { encoding.bom().len() } else { 0 };
+        let mut decoder = Decoder {
+            text: String::with_capacity(bytes.len()),
+            runs: vec![],
+            original: bom
+        };
+
+        let bytes = &bytes[bom..];
+
+        match encoding {
+            Encoding::Utf8 => decode_utf8(bytes, &mut decoder),
+            Encoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes, &mut decoder),
+            Encoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes, &mut decoder),
+            Encoding::Windows1252 => {
+                for &byte in bytes {
+                    let c = if (0x80..0xa0).contains(&byte) {
+                        WINDOWS_1252[byte as usize - 0x80]
+                    } else {
+                        byte as char
+                    };
+
+                    decoder.push(c, 1);
+                }
+            }
+        }
+
+        DecodedInput {
+            input: OwnedInput::new(decoder.text),
+            encoding,
+            runs: decoder.runs,
+            original_len: bom + bytes.len(),
+            lines: OnceLock::new(),
+            columns: Columns::Chars
+        }
+    }
+
+    /// Returns the encoding of the original bytes.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the decoded text.
+    pub fn as_str(&self) -> &str {
+        self.input.as_str()
+    }
+
+    /// Sets the unit in which [`line_col`](trait.Input#tymethod.line_col) counts columns. Defaults
+    /// to `Columns::Chars`.
+    pub fn set_columns(&mut self, columns: Columns) {
+        self.columns = columns;
+    }
+
+    /// Returns the byte offset in the original bytes of the decoded position `pos`.
+    pub fn original_pos(&self, pos: usize) -> usize {
+        if pos > self.input.len() {
+            panic!("position out of bounds");
+        }
+
+        if pos == self.input.len() {
+            return self.original_len;
+        }
+
+        let run = &self.runs[self.runs.partition_point(|run| run.start <= pos) - 1];
+
+        run.original + (pos - run.start) / run.len * run.original_len
+    }
+
+    #[inline]
+    fn line_index(&self) -> &LineIndex {
+        self.lines.get_or_init(|| LineIndex::new(self.input.as_str().as_bytes()))
+    }
+}
+
+fn decode_utf8(mut bytes: &[u8], decoder: &mut Decoder) {
+    while !bytes.is_empty() {
+        let (valid, invalid) = match str::from_utf8(bytes) {
+            Ok(valid) => (valid, 0),
+            Err(error) => {
+                let valid = str::from_utf8(&bytes[..error.valid_up_to()]).unwrap();
+
+                (valid, error.error_len().unwrap_or(bytes.len() - valid.len()))
+            }
+        };
+
+        for c in valid.chars() {
+            decoder.push(c, c.len_utf8());
+        }
+
+        if invalid > 0 {
+            decoder.push(char::REPLACEMENT_CHARACTER, invalid);
+        }
+
+        bytes = &bytes[valid.len() + invalid..];
+    }
+}
+
+fn decode_utf16<F>(bytes: &[u8], unit: F, decoder: &mut Decoder) where F: Fn([u8; 2]) -> u16 {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+
+    for result in char::decode_utf16(units) {
+        match result {
+            Ok(c) => decoder.push(c, c.len_utf16() * 2),
+            Err(_) => decoder.push(char::REPLACEMENT_CHARACTER, 2)
+        }
+    }
+
+    if bytes.len() % 2 == 1 {
+        decoder.push(char::REPLACEMENT_CHARACTER, 1);
+    }
+}
+
+impl<'a> Input<'a> for DecodedInput {
+    type Slice = SharedStr;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.input.at_end()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.input.pos()
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.input.set_pos(pos)
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> SharedStr {
+        self.input.slice(start, end)
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        let (line, start) = self.line_index().line_start(self.input.len(), pos);
+
+        let col = match self.columns {
+            Columns::Bytes => self.original_pos(pos) - self.original_pos(start),
+            columns => columns.count(&self.input.as_str()[start..pos])
+        };
+
+        (line, col + 1)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.input.match_string(string)
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, string: &str) -> bool {
+        self.input.match_insensitive(string)
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.input.match_range(left, right)
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        self.input.match_char_by(predicate)
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        self.input.match_any()
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.input.match_bytes(bytes)
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        self.input.match_byte_range(left, right)
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        self.input.match_slice(start, end)
+    }
+
+    #[inline]
+    fn match_trie(&mut self, trie: &Trie) -> bool {
+        self.input.match_trie(trie)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{Columns, Input};
+    use super::{DecodedInput, Encoding};
+
+    #[test]
+    fn windows_1252() {
+        let input = DecodedInput::new(b"\x93caf\xe9\x94 \x80\x81");
+
+        assert_eq!(input.encoding(), Encoding::Windows1252);
+        assert_eq!(input.as_str(), "\u{201c}café\u{201d} €\u{81}");
+        assert_eq!(input.original_pos(3), 1);
+        assert_eq!(input.original_pos(6), 4);
+        assert_eq!(input.original_pos(8), 5);
+        assert_eq!(input.original_pos(input.len()), 9);
+    }
+
+    #[test]
+    fn utf8() {
+        let input = DecodedInput::new(b"\xef\xbb\xbfa\xffb\xe5\x97");
+
+        assert_eq!(input.encoding(), Encoding::Utf8);
+        assert_eq!(input.as_str(), "a\u{fffd}b\u{fffd}");
+        assert_eq!(input.original_pos(0), 3);
+        assert_eq!(input.original_pos(4), 5);
+        assert_eq!(input.original_pos(5), 6);
+        assert_eq!(input.original_pos(8), 8);
+    }
+
+    #[test]
+    fn utf16() {
+        let bytes = b"\xfe\xff\x00a\xd8\x00\x00b\x00";
+        let input = DecodedInput::with_encoding(bytes, Encoding::Utf16Be);
+
+        assert_eq!(input.as_str(), "a\u{fffd}b\u{fffd}");
+        assert_eq!(input.original_pos(1), 4);
+        assert_eq!(input.original_pos(4), 6);
+        assert_eq!(input.original_pos(5), 8);
+        assert_eq!(input.original_pos(8), 9);
+    }
+
+    #[test]
+    fn empty() {
+        let input = DecodedInput::new(b"\xff\xfe");
+
+        assert!(input.is_empty());
+        assert_eq!(input.original_pos(0), 2);
+        assert_eq!(input.line_col(0), (1, 1));
+    }
+
+    #[test]
+    fn columns() {
+        let mut input = DecodedInput::new(b"\xff\xfeh\x00\n\x00\xe9\x00b\x00");
+
+        assert_eq!(input.line_col(4), (2, 2));
+
+        input.set_columns(Columns::Bytes);
+
+        assert_eq!(input.line_col(4), (2, 3));
+    }
+}
@@ -0,0 +1,231 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::super::{Input, Trie};
+
+/// A `struct` that wraps an `Input` holding a fragment of a larger file, e.g. a code block inside
+/// a Markdown document, so that all positions refer to the enclosing file.
+///
+/// Positions of an `EmbeddedInput` start at `offset`, which makes tokens and
+/// [`expected`](trait.Parser#tymethod.expected) positions relative to the enclosing file, while
+/// [`line_col`](trait.Input#tymethod.line_col) continues from the `line` and `col` where the
+/// fragment starts. Columns on the first line of the fragment are shifted by `col`, while the
+/// following lines start from column 1. The `source` identifies the enclosing file and can be
+/// anything, e.g. its name.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::EmbeddedInput;
+/// # use pest::Input;
+/// # use pest::StringInput;
+/// // "SELECT\n  1" starts at byte 120, line 7, col 15 of "query.rs"
+/// let mut input = EmbeddedInput::new(StringInput::new("SELECT\n  1"), "query.rs", 120, 7, 15);
+///
+/// assert!(input.match_string("SELECT"));
+/// assert_eq!(input.pos(), 126);
+/// assert_eq!(input.slice(120, 126), "SELECT");
+/// assert_eq!(input.line_col(126), (7, 21));
+/// assert_eq!(input.line_col(129), (8, 3));
+/// assert_eq!(*input.source(), "query.rs");
+/// ```
+pub struct EmbeddedInput<I, S> {
+    input: I,
+    source: S,
+    offset: usize,
+    line: usize,
+    col: usize
+}
+
+impl<I, S> EmbeddedInput<I, S> {
+    /// Creates a new `EmbeddedInput` from an `input` that starts at byte `offset`, 1-based `line`,
+    /// and 1-based `col` of the file identified by `source`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line` or `col` is 0.
+    pub fn new(input: I, source: S, offset: usize, line: usize, col: usize) -> EmbeddedInput<I, S> {
+        if line == 0 || col == 0 {
+            panic!("lines and columns start at 1");
+        }
+
+        EmbeddedInput {
+            input,
+            source,
+            offset,
+            line,
+            col
+        }
+    }
+
+    /// Returns the identifier of the enclosing file.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns the position where the fragment starts in the enclosing file.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the wrapped `Input`, whose positions are relative to the fragment.
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns the wrapped `Input` and drops the `EmbeddedInput`.
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    #[inline]
+    fn local(&self, pos: usize) -> usize {
+        match pos.checked_sub(self.offset) {
+            Some(pos) => pos,
+            None => panic!("position before the start of the fragment")
+        }
+    }
+}
+
+impl<'a, I: Input<'a>, S> Input<'a> for EmbeddedInput<I, S> {
+    type Slice = I::Slice;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[inline]
+    fn start_pos(&self) -> usize {
+        self.offset + self.input.start_pos()
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.input.at_end()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.offset + self.input.pos()
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        let pos = self.local(pos);
+
+        self.input.set_pos(pos)
+    }
+
+    const DISCARDS: bool = I::DISCARDS;
+
+    #[inline]
+    fn discard(&mut self, pos: usize) {
+        if let Some(pos) = pos.checked_sub(self.offset) {
+            self.input.discard(pos);
+        }
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> I::Slice {
+        self.input.slice(self.local(start), self.local(end))
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        match self.input.line_col(self.local(pos)) {
+            (1, col) => (self.line, self.col + col - 1),
+            (line, col) => (self.line + line - 1, col)
+        }
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.input.match_string(string)
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, string: &str) -> bool {
+        self.input.match_insensitive(string)
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.input.match_range(left, right)
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        self.input.match_char_by(predicate)
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        self.input.match_any()
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.input.match_bytes(bytes)
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        self.input.match_byte_range(left, right)
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        let start = self.local(start);
+        let end = self.local(end);
+
+        self.input.match_slice(start, end)
+    }
+
+    #[inline]
+    fn match_trie(&mut self, trie: &Trie) -> bool {
+        self.input.match_trie(trie)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::super::StringInput;
+    use super::EmbeddedInput;
+
+    #[test]
+    fn positions() {
+        let mut input = EmbeddedInput::new(StringInput::new("ab\ncd"), (), 10, 3, 5);
+
+        assert_eq!(input.start_pos(), 10);
+        assert_eq!(input.len(), 5);
+
+        input.set_pos(13);
+
+        assert!(input.match_string("cd"));
+        assert!(input.at_end());
+        assert!(!input.match_slice(13, 14));
+        assert_eq!(input.inner().pos(), 5);
+        assert_eq!(input.line_col(10), (3, 5));
+        assert_eq!(input.line_col(12), (3, 7));
+        assert_eq!(input.line_col(14), (4, 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn before_offset() {
+        let mut input = EmbeddedInput::new(StringInput::new("ab"), (), 10, 1, 1);
+
+        input.set_pos(9);
+    }
+}
@@ -0,0 +1,211 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A table of line starts, built once per input, that answers position queries with a binary
+/// search. `"\n"`, `"\r\n"`, and a lone `"\r"` all end a line.
+///
+/// The line of a `"\r\n"` is considered to end at the `'\r'`, so the `'\n'` does not take up a
+/// column in the following line.
+pub struct LineIndex {
+    // line starts together with whether they are at the '\n' of a "\r\n"
+    starts: Vec<(usize, bool)>
+}
+
+impl LineIndex {
+    pub fn new(bytes: &[u8]) -> LineIndex {
+        LineIndex::from_bytes(bytes.iter().cloned())
+    }
+
+    /// Builds a `LineIndex` from `bytes` that do not need to be contiguous in memory.
+    pub fn from_bytes<I: IntoIterator<Item = u8>>(bytes: I) -> LineIndex {
+        let mut starts = vec![(0, false)];
+        let mut after_cr = false;
+
+        for (i, byte) in bytes.into_iter().enumerate() {
+            match byte {
+                b'\n' if after_cr => starts.last_mut().unwrap().1 = true,
+                b'\r' | b'\n' => starts.push((i + 1, false)),
+                _ => ()
+            }
+
+            after_cr = byte == b'\r';
+        }
+
+        LineIndex {
+            starts
+        }
+    }
+
+    /// Returns the 1-based line of `pos` together with the position where its column counting
+    /// starts, in an input of `len` bytes.
+    pub fn line_start(&self, len: usize, pos: usize) -> (usize, usize) {
+        if pos > len {
+            panic!("position out of bounds");
+        }
+
+        let line = match self.starts.binary_search_by_key(&pos, |&(start, _)| start) {
+            Ok(i) => i,
+            Err(i) => i - 1
+        };
+
+        (line + 1, self.content_start(line, pos))
+    }
+
+    /// Returns the `(start, end)` positions of the 1-based `line`'s content, line ending excluded,
+    /// in an input of `len` bytes.
+    pub fn bounds(&self, len: usize, line: usize) -> Option<(usize, usize)> {
+        if line == 0 || line > self.starts.len() {
+            return None;
+        }
+
+        let start = self.content_start(line - 1, len);
+        let end = match self.starts.get(line) {
+            Some(&(next, _)) => next - 1,
+            None => len
+        };
+
+        Some((start, ::std::cmp::max(start, end)))
+    }
+
+    // Skips the '\n' of a "\r\n" that was split by a line start, unless `pos` comes before it.
+    fn content_start(&self, line: usize, pos: usize) -> usize {
+        let (start, crlf) = self.starts[line];
+
+        if crlf && start < pos {
+            start + 1
+        } else {
+            start
+        }
+    }
+}
+
+/// An `enum` that selects the unit in which columns are counted when turning positions into
+/// `(line, col)` pairs.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Columns;
+/// # use pest::Input;
+/// # use pest::StringInput;
+/// let mut input = StringInput::new("\t𝄞a");
+///
+/// assert_eq!(input.line_col(5), (1, 3));
+/// assert_eq!(input.line_col_in(5, Columns::Bytes), (1, 6));
+/// assert_eq!(input.line_col_in(5, Columns::Utf16), (1, 4));
+/// assert_eq!(input.line_col_in(5, Columns::Tabs(4)), (1, 6));
+///
+/// input.set_columns(Columns::Utf16);
+///
+/// assert_eq!(input.line_col(5), (1, 4));
+/// assert_eq!(input.pos_at(1, 4), Some(5));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Columns {
+    /// Unicode scalar values, i.e. `char`s; the default
+    #[default]
+    Chars,
+    /// UTF-8 bytes
+    Bytes,
+    /// UTF-16 code units, as used by the Language Server Protocol
+    Utf16,
+    /// display columns with tabs expanded to the next multiple of the given width
+    Tabs(usize)
+}
+
+impl Columns {
+    // Returns the 0-based column right after `c` when it starts at column `col`.
+    #[inline]
+    fn advance(self, col: usize, c: char) -> usize {
+        match self {
+            Columns::Chars => col + 1,
+            Columns::Bytes => col + c.len_utf8(),
+            Columns::Utf16 => col + c.len_utf16(),
+            Columns::Tabs(width) if c == '\t' && width > 0 => (col / width + 1) * width,
+            Columns::Tabs(_) => col + 1
+        }
+    }
+
+    /// Returns the 0-based column at the end of `text`.
+    pub fn count(self, text: &str) -> usize {
+        match self {
+            Columns::Chars => text.chars().count(),
+            Columns::Bytes => text.len(),
+            _ => text.chars().fold(0, |col, c| self.advance(col, c))
+        }
+    }
+
+    /// Returns the byte offset of the `char` that covers 0-based column `col` of `text`, the
+    /// length of `text` if `col` is right after its end, or `None` if it's further.
+    pub fn find(self, text: &str, col: usize) -> Option<usize> {
+        let mut current = 0;
+
+        for (i, c) in text.char_indices() {
+            let next = self.advance(current, c);
+
+            if col < next {
+                return Some(i);
+            }
+
+            current = next;
+        }
+
+        if col == current {
+            Some(text.len())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Columns, LineIndex};
+
+    #[test]
+    fn line_start() {
+        let bytes = b"a\rb\nc\r\nd";
+        let index = LineIndex::new(bytes);
+
+        assert_eq!(index.line_start(bytes.len(), 0), (1, 0));
+        assert_eq!(index.line_start(bytes.len(), 3), (2, 2));
+        assert_eq!(index.line_start(bytes.len(), 6), (4, 6));
+        assert_eq!(index.line_start(bytes.len(), 7), (4, 7));
+        assert_eq!(index.line_start(bytes.len(), 8), (4, 7));
+    }
+
+    #[test]
+    fn bounds() {
+        let bytes = b"a\rb\nc\r\nd";
+        let index = LineIndex::new(bytes);
+
+        assert_eq!(index.bounds(bytes.len(), 0), None);
+        assert_eq!(index.bounds(bytes.len(), 1), Some((0, 1)));
+        assert_eq!(index.bounds(bytes.len(), 3), Some((4, 5)));
+        assert_eq!(index.bounds(bytes.len(), 4), Some((7, 8)));
+        assert_eq!(index.bounds(bytes.len(), 5), None);
+    }
+
+    #[test]
+    fn columns() {
+        let text = "a\t𝄞\tb";
+
+        assert_eq!(Columns::Chars.count(text), 5);
+        assert_eq!(Columns::Bytes.count(text), 8);
+        assert_eq!(Columns::Utf16.count(text), 6);
+        assert_eq!(Columns::Tabs(4).count(text), 9);
+        assert_eq!(Columns::Tabs(0).count(text), 5);
+
+        assert_eq!(Columns::Utf16.find(text, 2), Some(2));
+        assert_eq!(Columns::Utf16.find(text, 3), Some(2));
+        assert_eq!(Columns::Utf16.find(text, 4), Some(6));
+        assert_eq!(Columns::Tabs(4).find(text, 3), Some(1));
+        assert_eq!(Columns::Tabs(4).find(text, 4), Some(2));
+        assert_eq!(Columns::Tabs(4).find(text, 9), Some(8));
+        assert_eq!(Columns::Tabs(4).find(text, 10), None);
+    }
+}
@@ -0,0 +1,144 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::str;
+
+use memmap2::Mmap;
+
+use super::StringInput;
+
+/// A `struct` that maps a UTF-8 file into memory instead of reading it into a `String`. It is
+/// only available with the `mmap` feature.
+///
+/// The file is validated as UTF-8 once when it's opened. Its [`input`](#method.input) is a
+/// `StringInput` that borrows the mapping, so it slices and answers `line_col` exactly like one
+/// created from a `&str`, and the mapping stays alive for as long as the `Parser`, its slices,
+/// or any `process!` results borrow from it.
+///
+/// Like any memory-mapped file, its contents must not be modified by other processes while it
+/// is mapped, since this would change text that was already validated and parsed.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::MappedFile;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         lines = { line* ~ eoi }
+///         line  = { (!["\n"] ~ any)* ~ ["\n"] }
+///     }
+/// }
+///
+/// let file = MappedFile::open("large.txt").unwrap();
+/// let mut parser = Rdp::new(file.input());
+///
+/// assert!(parser.lines());
+/// # }
+/// ```
+pub struct MappedFile {
+    map: Option<Mmap>
+}
+
+impl MappedFile {
+    /// Maps the file at `path` into memory. Fails with `io::ErrorKind::InvalidData` if the file is
+    /// not valid UTF-8.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<MappedFile> {
+        MappedFile::from_file(&File::open(path)?)
+    }
+
+    /// Maps an already opened `file` into memory. Fails with `io::ErrorKind::InvalidData` if the
+    /// file is not valid UTF-8.
+    pub fn from_file(file: &File) -> io::Result<MappedFile> {
+        // empty files cannot be mapped on every platform
+        let map = if file.metadata()?.len() == 0 {
+            None
+        } else {
+            // the file is expected not to be modified while it's mapped, as documented above
+            Some(unsafe { Mmap::map(file)? })
+        };
+
+        if let Some(ref map) = map {
+            if let Err(error) = str::from_utf8(map) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+            }
+        }
+
+        Ok(MappedFile {
+            map
+        })
+    }
+
+    /// Returns the text of a `MappedFile`.
+    pub fn as_str(&self) -> &str {
+        match self.map {
+            // validated once in from_file
+            Some(ref map) => unsafe { str::from_utf8_unchecked(map) },
+            None => ""
+        }
+    }
+
+    /// Returns a `StringInput` over the text of a `MappedFile`.
+    pub fn input(&self) -> StringInput<'_> {
+        StringInput::new(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::ErrorKind;
+
+    use super::super::super::Input;
+    use super::MappedFile;
+
+    #[test]
+    fn mapped() {
+        let path = env::temp_dir().join("pest_mapped_file_mapped.txt");
+
+        fs::write(&path, "ab\ncd").unwrap();
+
+        let file = MappedFile::open(&path).unwrap();
+        let mut input = file.input();
+
+        assert!(input.match_string("ab\nc"));
+        assert_eq!(input.slice(3, 5), "cd");
+        assert_eq!(input.line_col(4), (2, 2));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn empty() {
+        let path = env::temp_dir().join("pest_mapped_file_empty.txt");
+
+        fs::write(&path, "").unwrap();
+
+        assert_eq!(MappedFile::open(&path).unwrap().as_str(), "");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn invalid() {
+        let path = env::temp_dir().join("pest_mapped_file_invalid.txt");
+
+        fs::write(&path, b"a\xff").unwrap();
+
+        let error = MappedFile::open(&path).err().unwrap();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -5,6 +5,31 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+mod bytes_input;
+mod chars;
+mod chunked_input;
+mod decoded_input;
+mod embedded_input;
+mod line_index;
+#[cfg(feature = "mmap")]
+mod mapped_file;
+mod normalized_input;
+mod owned_input;
+mod reader_input;
+mod slice_input;
+mod source_map;
 mod string_input;
 
+pub use self::bytes_input::BytesInput;
+pub use self::chunked_input::ChunkedInput;
+pub use self::decoded_input::{DecodedInput, Encoding};
+pub use self::embedded_input::EmbeddedInput;
+pub use self::line_index::Columns;
+#[cfg(feature = "mmap")]
+pub use self::mapped_file::MappedFile;
+pub use self::normalized_input::{Normalization, NormalizedInput};
+pub use self::owned_input::{OwnedInput, SharedStr};
+pub use self::reader_input::ReaderInput;
+pub use self::slice_input::{KindInput, Lexeme, SliceInput};
+pub use self::source_map::{SourceId, SourceMap};
 pub use self::string_input::StringInput;
@@ -0,0 +1,353 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::OnceLock;
+
+use super::super::{Input, Trie};
+use super::line_index::{Columns, LineIndex};
+use super::{OwnedInput, SharedStr};
+
+/// A `struct` that selects what a [`NormalizedInput`](struct.NormalizedInput) removes from its
+/// text.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Normalization;
+/// let c = Normalization { splices: true, ..Normalization::default() };
+///
+/// assert!(c.bom && c.crlf);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Normalization {
+    /// removes a leading UTF-8 byte order mark; on by default
+    pub bom: bool,
+    /// turns `"\r\n"` line endings into `"\n"`; on by default
+    pub crlf: bool,
+    /// removes backslash-newline line continuations, like the C preprocessor; off by default
+    pub splices: bool
+}
+
+impl Default for Normalization {
+    fn default() -> Normalization {
+        Normalization {
+            bom: true,
+            crlf: true,
+            splices: false
+        }
+    }
+}
+
+/// A `struct` that presents a normalized view of a `&str` to the grammar, so that rules do not
+/// need to handle byte order marks, `"\r\n"` line endings, or line continuations, while keeping a
+/// map back to the original text. Its slices are [`SharedStr`](struct.SharedStr)s of the
+/// normalized text.
+///
+/// Positions, and thus tokens and [`expected`](trait.Parser#tymethod.expected) positions, are
+/// positions in the normalized text. [`original_pos`](#method.original_pos) and
+/// [`original_span`](#method.original_span) translate them to the original text, while
+/// [`line_col`](trait.Input#tymethod.line_col) directly returns lines and columns of the original
+/// text.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::NormalizedInput;
+/// let mut input = NormalizedInput::new("\u{feff}a\r\nb");
+///
+/// assert_eq!(input.as_str(), "a\nb");
+/// assert!(input.match_string("a\nb"));
+/// assert_eq!(input.original_pos(2), 6);
+/// assert_eq!(input.line_col(2), (2, 1));
+/// ```
+pub struct NormalizedInput<'a> {
+    original: &'a str,
+    input: OwnedInput,
+    // normalized positions where the distance to the original positions changes, together with
+    // their original positions
+    map: Vec<(usize, usize)>,
+    lines: OnceLock<LineIndex>,
+    columns: Columns
+}
+
+impl<'a> NormalizedInput<'a> {
+    /// Creates a new `NormalizedInput` with the default [`Normalization`](struct.Normalization),
+    /// which removes a byte order mark and turns `"\r\n"` into `"\n"`.
+    pub fn new(original: &'a str) -> NormalizedInput<'a> {
+        NormalizedInput::with_normalization(original, Normalization::default())
+    }
+
+    /// Creates a new `NormalizedInput` that applies `normalization`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Input;
+    /// # use pest::Normalization;
+    /// # use pest::NormalizedInput;
+    /// let normalization = Normalization { splices: true, ..Normalization::default() };
+    /// let input = NormalizedInput::with_normalization("#define A \\\r\n  1", normalization);
+    ///
+    /// assert_eq!(input.as_str(), "#define A   1");
+    /// assert_eq!(input.original_pos(10), 13);
+    /// assert_eq!(input.line_col(10), (2, 1));
+    /// ```
+    pub fn with_normalization(original: &'a str,
+                              normalization: Normalization) -> NormalizedInput<'a> {
+        let bytes = original.as_bytes();
+        let mut text = String::with_capacity(original.len());
+        let mut map = vec![(0, 0)];
+
+        let mut i = 0;
+
+        if normalization.bom && original.starts_with('\u{feff}') {
+            i = '\u{feff}'.len_utf8();
+            map[0].1 = i;
+        }
+
+        let mut copied = i;
+
+        while i < bytes.len() {
+            let (removed, replacement) = match bytes[i] {
+                b'\\' if normalization.splices => {
+                    if bytes[i + 1..].starts_with(b"\n") {
+                        (2, "")
+                    } else if bytes[i + 1..].starts_with(b"\r\n") {
+                        (3, "")
+                    } else {
+                        (0, "")
+                    }
+                },
+                b'\r' if normalization.crlf && bytes[i + 1..].starts_with(b"\n") => (2, "\n"),
+                _ => (0, "")
+            };
+
+            if removed > 0 {
+                text.push_str(&original[copied..i]);
+                text.push_str(replacement);
+
+                i += removed;
+                copied = i;
+
+                map.push((text.len(), i));
+            } else {
+                i += 1;
+            }
+        }
+
+        text.push_str(&original[copied..]);
+
+        NormalizedInput {
+            original,
+            input: OwnedInput::new(text),
+            map,
+            lines: OnceLock::new(),
+            columns: Columns::Chars
+        }
+    }
+
+    /// Returns the normalized text.
+    pub fn as_str(&self) -> &str {
+        self.input.as_str()
+    }
+
+    /// Returns the original text.
+    pub fn original(&self) -> &'a str {
+        self.original
+    }
+
+    /// Sets the unit in which [`line_col`](trait.Input#tymethod.line_col) counts columns of the
+    /// original text. Defaults to `Columns::Chars`.
+    pub fn set_columns(&mut self, columns: Columns) {
+        self.columns = columns;
+    }
+
+    /// Returns the position in the original text of the normalized position `pos`. A `'\n'`
+    /// that replaced a `"\r\n"` is mapped to its `'\r'`, while a position right after removed
+    /// text, like a line continuation, is mapped after the removed text as well.
+    pub fn original_pos(&self, pos: usize) -> usize {
+        if pos > self.input.len() {
+            panic!("position out of bounds");
+        }
+
+        let i = self.map.partition_point(|&(start, _)| start <= pos) - 1;
+        let (start, original) = self.map[i];
+
+        original + pos - start
+    }
+
+    /// Returns the span in the original text of the normalized span between `start` and `end`.
+    /// Unlike its start, the end of a span is mapped right after its last byte, so that a span
+    /// never covers text that was removed right after it, but covers all of a `"\r\n"` that
+    /// was turned into its last `'\n'`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::NormalizedInput;
+    /// let input = NormalizedInput::new("ab\r\ncd");
+    ///
+    /// assert_eq!(input.original_span(0, 2), (0, 2));
+    /// assert_eq!(input.original_span(2, 3), (2, 4));
+    /// ```
+    pub fn original_span(&self, start: usize, end: usize) -> (usize, usize) {
+        if start >= end {
+            let start = self.original_pos(start);
+
+            return (start, start);
+        }
+
+        let last = self.original_pos(end - 1);
+        let replaced = self.input.as_str().as_bytes()[end - 1] == b'\n' &&
+                       self.original.as_bytes()[last] == b'\r';
+
+        (self.original_pos(start), if replaced { last + 2 } else { last + 1 })
+    }
+
+    #[inline]
+    fn line_index(&self) -> &LineIndex {
+        self.lines.get_or_init(|| LineIndex::new(self.original.as_bytes()))
+    }
+}
+
+impl<'a> Input<'a> for NormalizedInput<'a> {
+    type Slice = SharedStr;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.input.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.input.at_end()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.input.pos()
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.input.set_pos(pos)
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> SharedStr {
+        self.input.slice(start, end)
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        let pos = self.original_pos(pos);
+        let (line, start) = self.line_index().line_start(self.original.len(), pos);
+
+        // a leading byte order mark is not a column
+        let start = if start == 0 && self.map[0].1 > 0 { self.map[0].1 } else { start };
+
+        (line, self.columns.count(&self.original[start..pos]) + 1)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.input.match_string(string)
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, string: &str) -> bool {
+        self.input.match_insensitive(string)
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.input.match_range(left, right)
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        self.input.match_char_by(predicate)
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        self.input.match_any()
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.input.match_bytes(bytes)
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        self.input.match_byte_range(left, right)
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        self.input.match_slice(start, end)
+    }
+
+    #[inline]
+    fn match_trie(&mut self, trie: &Trie) -> bool {
+        self.input.match_trie(trie)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::{Normalization, NormalizedInput};
+
+    #[test]
+    fn unchanged() {
+        let input = NormalizedInput::with_normalization("a\r\nb\\\n", Normalization {
+            bom: false,
+            crlf: false,
+            splices: false
+        });
+
+        assert_eq!(input.as_str(), "a\r\nb\\\n");
+        assert_eq!(input.original_pos(6), 6);
+        assert_eq!(input.line_col(3), (2, 1));
+    }
+
+    #[test]
+    fn map() {
+        let normalization = Normalization { splices: true, ..Normalization::default() };
+        let input = NormalizedInput::with_normalization("\u{feff}a\\\n\\\r\nb\r\n\r\nc\\",
+                                                        normalization);
+
+        assert_eq!(input.as_str(), "ab\n\nc\\");
+        assert_eq!(input.original_pos(0), 3);
+        assert_eq!(input.original_pos(1), 9);
+        assert_eq!(input.original_pos(2), 10);
+        assert_eq!(input.original_pos(3), 12);
+        assert_eq!(input.original_pos(6), 16);
+        assert_eq!(input.original_span(0, 1), (3, 4));
+        assert_eq!(input.original_span(1, 2), (9, 10));
+        assert_eq!(input.original_span(1, 4), (9, 14));
+        assert_eq!(input.original_span(4, 6), (14, 16));
+    }
+
+    #[test]
+    fn line_col() {
+        let input = NormalizedInput::new("\u{feff}ab\r\ncd");
+
+        assert_eq!(input.line_col(0), (1, 1));
+        assert_eq!(input.line_col(2), (1, 3));
+        assert_eq!(input.line_col(3), (2, 1));
+        assert_eq!(input.line_col(5), (2, 3));
+    }
+}
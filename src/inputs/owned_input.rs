@@ -0,0 +1,363 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+
+use super::super::{Input, Trie};
+use super::line_index::{Columns, LineIndex};
+use super::StringInput;
+
+/// A `struct` that owns its text through an `Arc<str>`, so that a `Parser` built on top of it
+/// does not borrow anything and can be stored or sent to other threads freely. Its slices are
+/// [`SharedStr`](struct.SharedStr)s that share the same text.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::OwnedInput;
+/// let mut input = OwnedInput::new(String::from("asdasdf"));
+///
+/// assert!(input.match_string("asd"));
+///
+/// let slice = input.slice(3, 7);
+///
+/// drop(input);
+///
+/// assert_eq!(slice, "asdf");
+/// ```
+pub struct OwnedInput {
+    string: Arc<str>,
+    pos: usize,
+    lines: OnceLock<LineIndex>,
+    columns: Columns
+}
+
+impl OwnedInput {
+    /// Creates a new `OwnedInput` from anything that converts into an `Arc<str>`, like a `String`,
+    /// a `&str`, or an `Arc<str>` that is already shared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use pest::Input;
+    /// # use pest::OwnedInput;
+    /// let string: Arc<str> = Arc::from("asd");
+    /// let input = OwnedInput::new(string.clone());
+    ///
+    /// assert_eq!(input.len(), 3);
+    /// ```
+    pub fn new<S: Into<Arc<str>>>(string: S) -> OwnedInput {
+        OwnedInput {
+            string: string.into(),
+            pos: 0,
+            lines: OnceLock::new(),
+            columns: Columns::Chars
+        }
+    }
+
+    /// Returns the whole text of an `OwnedInput`.
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    /// Sets the unit in which [`line_col`](trait.Input#tymethod.line_col) counts columns. Defaults
+    /// to `Columns::Chars`.
+    pub fn set_columns(&mut self, columns: Columns) {
+        self.columns = columns;
+    }
+
+    /// Returns the text of a 1-based `line` without its line ending, or `None` if the line does
+    /// not exist.
+    pub fn line(&self, line: usize) -> Option<SharedStr> {
+        let (start, end) = self.line_index().bounds(self.string.len(), line)?;
+
+        Some(self.slice(start, end))
+    }
+
+    #[inline]
+    fn line_index(&self) -> &LineIndex {
+        self.lines.get_or_init(|| LineIndex::new(self.string.as_bytes()))
+    }
+
+    // Runs `f` on a `StringInput` borrowing the text at the current position.
+    #[inline]
+    fn view<F>(&mut self, f: F) -> bool where F: FnOnce(&mut StringInput) -> bool {
+        let mut input = StringInput::new(&self.string);
+
+        input.set_pos(self.pos);
+
+        let result = f(&mut input);
+
+        self.pos = input.pos();
+
+        result
+    }
+}
+
+impl<'a> Input<'a> for OwnedInput {
+    type Slice = SharedStr;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.string.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.string.is_empty()
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.pos == self.string.len()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> SharedStr {
+        // checks bounds and char boundaries
+        let _ = &self.string[start..end];
+
+        SharedStr {
+            string: self.string.clone(),
+            start,
+            end
+        }
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        let (line, start) = self.line_index().line_start(self.string.len(), pos);
+
+        (line, self.columns.count(&self.string[start..pos]) + 1)
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.view(|input| input.match_string(string))
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, string: &str) -> bool {
+        self.view(|input| input.match_insensitive(string))
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        self.view(|input| input.match_range(left, right))
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        self.view(|input| input.match_char_by(predicate))
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        self.view(|input| input.match_any())
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.view(|input| input.match_bytes(bytes))
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        self.view(|input| input.match_byte_range(left, right))
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        self.view(|input| input.match_slice(start, end))
+    }
+
+    #[inline]
+    fn match_trie(&mut self, trie: &Trie) -> bool {
+        self.view(|input| input.match_trie(trie))
+    }
+}
+
+/// A `struct` representing a slice of an [`OwnedInput`](struct.OwnedInput). It shares the text of
+/// the input instead of copying it, so cloning it is cheap, and it dereferences to a `&str`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::OwnedInput;
+/// let input = OwnedInput::new("hello world");
+/// let world = input.slice(6, 11);
+///
+/// assert_eq!(world.len(), 5);
+/// assert_eq!(world.to_uppercase(), "WORLD");
+/// assert_eq!(world.start(), 6);
+/// ```
+#[derive(Clone)]
+pub struct SharedStr {
+    string: Arc<str>,
+    start: usize,
+    end: usize
+}
+
+impl SharedStr {
+    /// Returns the slice as a `&str`.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.string[self.start..self.end]
+    }
+
+    /// Returns the starting position of the slice in its input.
+    #[inline]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the ending position of the slice in its input.
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SharedStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for SharedStr {
+    #[inline]
+    fn eq(&self, other: &SharedStr) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SharedStr {}
+
+impl PartialEq<str> for SharedStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for SharedStr {
+    #[inline]
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for SharedStr {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl Hash for SharedStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl From<SharedStr> for String {
+    #[inline]
+    fn from(slice: SharedStr) -> String {
+        slice.as_str().to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::super::super::Input;
+    use super::{OwnedInput, SharedStr};
+
+    #[test]
+    fn matching() {
+        let mut input = OwnedInput::new("asd嗨ASDF");
+
+        assert!(input.match_string("asd"));
+        assert!(input.match_range('一', '龥'));
+        assert!(input.match_insensitive("asdf"));
+        assert!(input.at_end());
+
+        input.set_pos(0);
+
+        assert!(input.match_any());
+        assert!(!input.match_slice(0, 1));
+        assert_eq!(input.pos(), 1);
+    }
+
+    #[test]
+    fn slices() {
+        let input = OwnedInput::new(String::from("a\nbc"));
+        let slice = input.slice(2, 4);
+        let set: HashSet<SharedStr> = vec![slice.clone(), input.slice(2, 4)].into_iter().collect();
+
+        assert_eq!(set.len(), 1);
+        assert!(set.contains("bc"));
+        assert_eq!(format!("{:?} {}", slice, slice), "\"bc\" bc");
+        assert_eq!(input.line(2).unwrap(), slice);
+        assert_eq!(input.line_col(3), (2, 2));
+        assert_eq!(String::from(slice), "bc");
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_inside_char() {
+        OwnedInput::new("嗨").slice(0, 1);
+    }
+}
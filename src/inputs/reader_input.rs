@@ -0,0 +1,534 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cell::RefCell;
+use std::io::{self, Read};
+
+use super::super::Input;
+use super::chars;
+
+const CHUNK: usize = 8 * 1024;
+
+struct Buffer<R> {
+    reader: R,
+    bytes: Vec<u8>,
+    offset: usize,
+    // positions before `start` count as discarded, even if they are still buffered
+    start: usize,
+    line_col: (usize, usize),
+    after_cr: bool,
+    eof: bool,
+    error: Option<io::Error>
+}
+
+impl<R: Read> Buffer<R> {
+    // Reads until position `to` is buffered or the reader is exhausted; returns whether `to` is
+    // buffered.
+    fn fill(&mut self, to: usize) -> bool {
+        while self.offset + self.bytes.len() < to && !self.eof {
+            let len = self.bytes.len();
+
+            self.bytes.resize(len + CHUNK, 0);
+
+            match self.reader.read(&mut self.bytes[len..]) {
+                Ok(0) => {
+                    self.bytes.truncate(len);
+                    self.eof = true;
+                }
+                Ok(read) => self.bytes.truncate(len + read),
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {
+                    self.bytes.truncate(len);
+                }
+                Err(error) => {
+                    self.bytes.truncate(len);
+                    self.eof = true;
+                    self.error = Some(error);
+                }
+            }
+        }
+
+        self.offset + self.bytes.len() >= to
+    }
+
+    // Returns whether `pos` was not discarded, recording an error otherwise.
+    fn check(&mut self, pos: usize) -> bool {
+        if pos < self.start {
+            if self.error.is_none() {
+                let message = format!("position {} was discarded; the input only starts at {}",
+                                      pos, self.start);
+
+                self.error = Some(io::Error::new(io::ErrorKind::InvalidInput, message));
+            }
+
+            false
+        } else {
+            true
+        }
+    }
+
+    // Returns how many bytes are buffered from `pos` on, which is none if `pos` was discarded.
+    fn available(&mut self, pos: usize) -> usize {
+        if self.check(pos) {
+            self.offset + self.bytes.len() - pos
+        } else {
+            0
+        }
+    }
+
+    // Returns the buffered bytes between `start` and `end`, which are none if `start` was
+    // discarded.
+    fn bytes(&mut self, start: usize, end: usize) -> &[u8] {
+        if self.check(start) {
+            &self.bytes[start - self.offset..end - self.offset]
+        } else {
+            &[]
+        }
+    }
+}
+
+/// A `struct` that matches text streamed from any `io::Read`, like a file that is too big to be
+/// read into memory at once. Its slices are owned `String`s.
+///
+/// The stream is read in chunks as matching advances and everything read is kept in memory until
+/// it is dropped with [`discard`](trait.Input#method.discard). Parsers generated by
+/// [`impl_rdp!`](macro.impl_rdp!) do this on their own, keeping everything from the oldest
+/// position they may still rewind to, the first `Token` in their queue, or the first capture on
+/// their stack, whichever comes first. Processing and clearing the queue between top-level rules
+/// therefore keeps the window small.
+///
+/// Backtracking with [`set_pos`](trait.Input#tymethod.set_pos) anywhere inside this window is
+/// free. Rewinding, slicing, or matching before it fails instead and records an error that is
+/// returned by [`take_error`](#method.take_error).
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::ReaderInput;
+/// let mut input = ReaderInput::new("a\nb\nc".as_bytes());
+///
+/// assert!(input.match_string("a\n"));
+///
+/// input.discard(2);
+///
+/// assert!(input.match_string("b\nc"));
+/// assert!(input.at_end());
+/// assert_eq!(input.slice(2, 5), "b\nc");
+/// assert_eq!(input.line_col(4), (3, 1));
+///
+/// input.set_pos(0);
+///
+/// assert!(!input.match_string("a"));
+/// assert!(input.take_error().is_some());
+/// ```
+pub struct ReaderInput<R> {
+    buffer: RefCell<Buffer<R>>,
+    pos: usize
+}
+
+impl<R: Read> ReaderInput<R> {
+    /// Creates a new `ReaderInput` from an `io::Read`. Reading starts lazily, on the first match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Input;
+    /// # use pest::ReaderInput;
+    /// let input = ReaderInput::new("asd".as_bytes());
+    ///
+    /// assert_eq!(input.pos(), 0);
+    /// ```
+    pub fn new(reader: R) -> ReaderInput<R> {
+        ReaderInput {
+            buffer: RefCell::new(Buffer {
+                reader,
+                bytes: vec![],
+                offset: 0,
+                start: 0,
+                line_col: (1, 1),
+                after_cr: false,
+                eof: false,
+                error: None
+            }),
+            pos: 0
+        }
+    }
+
+    /// Returns the position before which everything was discarded.
+    pub fn discarded(&self) -> usize {
+        self.buffer.borrow().start
+    }
+
+    /// Returns the I/O error that ended reading, if any, in which case the `Input` behaves as if
+    /// the stream ended right before the error. Otherwise, returns the error of the first attempt
+    /// to use a position that was already discarded, if any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.buffer.get_mut().error.take()
+    }
+
+    #[inline]
+    fn compare<F>(&mut self, len: usize, compare: F) -> bool
+        where F: FnOnce(&[u8]) -> bool {
+
+        let to = self.pos + len;
+        let buffer = self.buffer.get_mut();
+
+        if buffer.check(self.pos) && buffer.fill(to) {
+            let result = compare(buffer.bytes(self.pos, to));
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+}
+
+impl<'a, R: Read> Input<'a> for ReaderInput<R> {
+    type Slice = String;
+
+    /// Returns length of a `ReaderInput`, reading the whole stream in order to find it.
+    #[inline]
+    fn len(&self) -> usize {
+        let mut buffer = self.buffer.borrow_mut();
+
+        buffer.fill(usize::MAX);
+
+        buffer.offset + buffer.bytes.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        !self.buffer.borrow_mut().fill(1)
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        !self.buffer.borrow_mut().fill(self.pos + 1)
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Sets current position of a `ReaderInput`. Setting it before the
+    /// [discarded](trait.Input#method.discard) prefix records an error, and matching there fails.
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.buffer.get_mut().check(pos);
+
+        self.pos = pos
+    }
+
+    const DISCARDS: bool = true;
+
+    /// Discards everything before `pos`. Positions keep counting from the start of the stream.
+    /// Memory is released in chunks rather than on every call.
+    fn discard(&mut self, pos: usize) {
+        let buffer = self.buffer.get_mut();
+
+        if pos <= buffer.start {
+            return
+        }
+
+        buffer.fill(pos);
+        buffer.start = ::std::cmp::min(pos, buffer.offset + buffer.bytes.len());
+
+        let len = buffer.start - buffer.offset;
+
+        if len < CHUNK && len < buffer.bytes.len() {
+            return
+        }
+
+        for &byte in &buffer.bytes[..len] {
+            match byte {
+                b'\n' if buffer.after_cr => (),
+                b'\r' | b'\n' => buffer.line_col = (buffer.line_col.0 + 1, 1),
+                byte if byte & 0xc0 != 0x80 => buffer.line_col.1 += 1,
+                _ => ()
+            }
+
+            buffer.after_cr = byte == b'\r';
+        }
+
+        buffer.bytes.drain(..len);
+        buffer.offset += len;
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> String {
+        let mut buffer = self.buffer.borrow_mut();
+
+        buffer.fill(end);
+
+        String::from_utf8_lossy(buffer.bytes(start, end)).into_owned()
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        let mut buffer = self.buffer.borrow_mut();
+
+        if !buffer.fill(pos) {
+            panic!("position out of bounds");
+        }
+
+        buffer.check(pos);
+
+        let mut line_col = buffer.line_col;
+        let mut after_cr = buffer.after_cr;
+        let end = ::std::cmp::max(pos, buffer.offset) - buffer.offset;
+
+        for &byte in &buffer.bytes[..end] {
+            match byte {
+                b'\n' if after_cr => (),
+                b'\r' | b'\n' => line_col = (line_col.0 + 1, 1),
+                byte if byte & 0xc0 != 0x80 => line_col.1 += 1,
+                _ => ()
+            }
+
+            after_cr = byte == b'\r';
+        }
+
+        line_col
+    }
+
+    #[inline]
+    fn match_string(&mut self, string: &str) -> bool {
+        self.match_bytes(string.as_bytes())
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, string: &str) -> bool {
+        let buffer = self.buffer.get_mut();
+
+        // case folding changes UTF-8 lengths by at most a few bytes per `char`
+        buffer.fill(self.pos + 4 * string.len());
+
+        let len = buffer.available(self.pos);
+        let bytes = buffer.bytes(self.pos, self.pos + len);
+
+        if let Some(len) = chars::match_insensitive(bytes, string) {
+            self.pos += len;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        let buffer = self.buffer.get_mut();
+
+        buffer.fill(self.pos + 4);
+
+        let len = buffer.available(self.pos);
+
+        if let Some((c, len)) = chars::first_char(buffer.bytes(self.pos, self.pos + len)) {
+            let result = left <= c && c <= right;
+
+            if result {
+                self.pos += len;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        let buffer = self.buffer.get_mut();
+
+        buffer.fill(self.pos + 4);
+
+        let len = buffer.available(self.pos);
+
+        if let Some((c, len)) = chars::first_char(buffer.bytes(self.pos, self.pos + len)) {
+            let result = predicate(c);
+
+            if result {
+                self.pos += len;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        let buffer = self.buffer.get_mut();
+
+        buffer.fill(self.pos + 4);
+
+        let len = buffer.available(self.pos);
+
+        match chars::first_char(buffer.bytes(self.pos, self.pos + len)) {
+            Some((_, len)) => self.pos += len,
+            None if len > 0 => self.pos += 1,
+            None => return false
+        }
+
+        true
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        self.compare(bytes.len(), |slice| slice == bytes)
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        self.compare(1, |bytes| left <= bytes[0] && bytes[0] <= right)
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        let to = self.pos + (end - start);
+        let buffer = self.buffer.get_mut();
+
+        if buffer.check(start) && buffer.check(self.pos) && buffer.fill(to) {
+            let offset = buffer.offset;
+            let bytes = &buffer.bytes;
+            let result = bytes[start - offset..end - offset] ==
+                         bytes[self.pos - offset..to - offset];
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use super::super::super::Input;
+    use super::ReaderInput;
+
+    // Hands out one byte per read in order to exercise refilling.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                Ok(0)
+            } else {
+                buf[0] = self.0[0];
+                self.0 = &self.0[1..];
+
+                Ok(1)
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut input = ReaderInput::new(Trickle(b""));
+
+        assert!(input.is_empty());
+        assert!(input.at_end());
+        assert!(input.match_string(""));
+        assert!(!input.match_string("a"));
+    }
+
+    #[test]
+    fn refill() {
+        let mut input = ReaderInput::new(Trickle("asd嗨asdf".as_bytes()));
+
+        assert!(input.match_string("asd"));
+        assert!(input.match_range('一', '龥'));
+        assert!(input.match_insensitive("ASDF"));
+        assert!(input.at_end());
+        assert_eq!(input.len(), 10);
+        assert_eq!(input.slice(0, 6), "asd嗨");
+    }
+
+    #[test]
+    fn backtrack_in_window() {
+        let mut input = ReaderInput::new(Trickle(b"abcabc"));
+
+        assert!(input.match_string("abc"));
+
+        input.discard(3);
+        input.set_pos(3);
+
+        assert!(input.match_string("ab"));
+
+        input.set_pos(3);
+
+        assert!(input.match_string("abc"));
+        assert_eq!(input.discarded(), 3);
+    }
+
+    #[test]
+    fn rewind_past_discarded() {
+        let mut input = ReaderInput::new(Trickle(b"abcabc"));
+
+        assert!(input.match_string("abc"));
+
+        input.discard(3);
+        input.set_pos(1);
+
+        assert!(!input.match_string("bc"));
+        assert!(!input.match_any());
+        assert!(!input.match_slice(0, 1));
+        assert_eq!(input.slice(1, 3), "");
+
+        let error = input.take_error().unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "position 1 was discarded; the input only starts at 3");
+
+        input.set_pos(3);
+
+        assert!(input.match_string("abc"));
+        assert!(input.take_error().is_none());
+    }
+
+    #[test]
+    fn line_col() {
+        let mut input = ReaderInput::new(Trickle("a\rb\nc\r\nd嗨".as_bytes()));
+
+        assert_eq!(input.line_col(3), (2, 2));
+
+        input.discard(6);
+
+        assert_eq!(input.line_col(6), (4, 1));
+        assert_eq!(input.line_col(7), (4, 1));
+        assert_eq!(input.line_col(8), (4, 2));
+        assert_eq!(input.line_col(11), (4, 3));
+    }
+
+    #[test]
+    fn error() {
+        struct Failing;
+
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+        }
+
+        let mut input = ReaderInput::new(Failing);
+
+        assert!(!input.match_string("a"));
+        assert!(input.take_error().is_some());
+    }
+}
@@ -0,0 +1,320 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::super::Input;
+
+/// A `trait` implemented by the elements of a [`SliceInput`](struct.SliceInput), usually tokens
+/// produced by a separate lexer, which are matched in `grammar!` by their kind with
+/// `[kind(...)]`.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Lexeme;
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// enum Kind {
+///     Number,
+///     Plus
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Lexed<'a> {
+///     kind: Kind,
+///     text: &'a str
+/// }
+///
+/// impl<'a> Lexeme for Lexed<'a> {
+///     type Kind = Kind;
+///
+///     fn kind(&self) -> Kind {
+///         self.kind
+///     }
+/// }
+/// ```
+pub trait Lexeme {
+    /// The type of the kind of an element, usually an `enum`.
+    type Kind: PartialEq + 'static;
+
+    /// Returns the kind of an element.
+    fn kind(&self) -> Self::Kind;
+}
+
+/// A `trait` implemented by inputs whose elements can be matched by their kind, which is what
+/// `[kind(...)]` uses in `grammar!`. Since the `Kind` is fixed by the input, matching a kind of
+/// another type does not compile.
+///
+/// Parsers generated by [`impl_rdp!`](macro.impl_rdp!) require it when started with a `kind:`
+/// line after the `slice:` line.
+///
+/// # Examples
+///
+/// ```compile_fail
+/// # use pest::KindInput;
+/// # use pest::Lexeme;
+/// # use pest::SliceInput;
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// enum Kind {
+///     Number
+/// }
+///
+/// impl Lexeme for Kind {
+///     type Kind = Kind;
+///
+///     fn kind(&self) -> Kind {
+///         *self
+///     }
+/// }
+///
+/// let lexed = [Kind::Number];
+/// let mut input = SliceInput::new(&lexed);
+///
+/// input.match_kind(&0u32);
+/// ```
+pub trait KindInput<'a>: Input<'a> {
+    /// The type of the kinds of the elements.
+    type Kind: PartialEq;
+
+    /// Matches if an `Input`'s current element has the `kind`, and advances the position with one
+    /// element in case it did.
+    fn match_kind(&mut self, kind: &Self::Kind) -> bool;
+}
+
+/// A `struct` useful for parsing a `&[T]` of elements, e.g. the tokens produced by a separate
+/// lexer. Its positions are indices of elements and its slices are `&[T]`.
+///
+/// Elements are matched by their [`Lexeme::kind`](trait.Lexeme#tymethod.kind) with
+/// [`KindInput::match_kind`](trait.KindInput#tymethod.match_kind), which is what `[kind(...)]`
+/// uses in `grammar!`, and `any` matches any single element. Strings, `char`s, and bytes never
+/// match, while the stack rules compare whole elements. Since elements have no lines,
+/// [`line_col`](trait.Input#tymethod.line_col) returns line 1 and the 1-based index of an element
+/// as its column.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Input;
+/// # use pest::KindInput;
+/// # use pest::Lexeme;
+/// # use pest::SliceInput;
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// enum Kind {
+///     Number,
+///     Plus
+/// }
+///
+/// impl Lexeme for Kind {
+///     type Kind = Kind;
+///
+///     fn kind(&self) -> Kind {
+///         *self
+///     }
+/// }
+///
+/// let lexed = [Kind::Number, Kind::Plus, Kind::Number];
+/// let mut input = SliceInput::new(&lexed);
+///
+/// assert!(input.match_kind(&Kind::Number));
+/// assert!(!input.match_kind(&Kind::Number));
+/// assert!(input.match_kind(&Kind::Plus));
+/// assert!(!input.match_string("1"));
+/// assert_eq!(input.slice(0, 2), &lexed[..2]);
+/// ```
+pub struct SliceInput<'a, T: 'a> {
+    elements: &'a [T],
+    pos: usize
+}
+
+impl<'a, T> SliceInput<'a, T> {
+    /// Creates a new `SliceInput` from a `&[T]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::SliceInput;
+    /// let input = SliceInput::new(&[1, 2, 3]);
+    ///
+    /// assert_eq!(input.get(1), Some(&2));
+    /// assert_eq!(input.get(3), None);
+    /// ```
+    pub fn new(elements: &'a [T]) -> SliceInput<'a, T> {
+        SliceInput {
+            elements,
+            pos: 0
+        }
+    }
+
+    /// Returns the element at position `pos`, or `None` if `pos` is at or past the end.
+    pub fn get(&self, pos: usize) -> Option<&'a T> {
+        self.elements.get(pos)
+    }
+}
+
+impl<'a, T: Lexeme + PartialEq> Input<'a> for SliceInput<'a, T> {
+    type Slice = &'a [T];
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.pos == self.elements.len()
+    }
+
+    #[inline]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    fn set_pos(&mut self, pos: usize) {
+        self.pos = pos
+    }
+
+    #[inline]
+    fn slice(&self, start: usize, end: usize) -> &'a [T] {
+        &self.elements[start..end]
+    }
+
+    #[inline]
+    fn line_col(&self, pos: usize) -> (usize, usize) {
+        if pos > self.elements.len() {
+            panic!("position out of bounds");
+        }
+
+        (1, pos + 1)
+    }
+
+    #[inline]
+    fn match_string(&mut self, _string: &str) -> bool {
+        false
+    }
+
+    #[inline]
+    fn match_insensitive(&mut self, _string: &str) -> bool {
+        false
+    }
+
+    #[inline]
+    fn match_range(&mut self, _left: char, _right: char) -> bool {
+        false
+    }
+
+    #[inline]
+    fn match_char_by<F>(&mut self, _predicate: F) -> bool where F: FnOnce(char) -> bool {
+        false
+    }
+
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        if self.pos < self.elements.len() {
+            self.pos += 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_bytes(&mut self, _bytes: &[u8]) -> bool {
+        false
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, _left: u8, _right: u8) -> bool {
+        false
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        let len = end - start;
+        let to = self.pos + len;
+
+        if to <= self.elements.len() && self.elements[start..end] == self.elements[self.pos..to] {
+            self.pos = to;
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<'a, T: Lexeme + PartialEq> KindInput<'a> for SliceInput<'a, T> {
+    type Kind = T::Kind;
+
+    #[inline]
+    fn match_kind(&mut self, kind: &T::Kind) -> bool {
+        let result = match self.elements.get(self.pos) {
+            Some(element) => element.kind() == *kind,
+            None => false
+        };
+
+        if result {
+            self.pos += 1;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::{KindInput, Lexeme, SliceInput};
+
+    #[derive(Debug, PartialEq)]
+    enum Lexed {
+        Ident(&'static str),
+        Comma
+    }
+
+    impl Lexeme for Lexed {
+        type Kind = u8;
+
+        fn kind(&self) -> u8 {
+            match *self {
+                Lexed::Ident(_) => 0,
+                Lexed::Comma => 1
+            }
+        }
+    }
+
+    #[test]
+    fn kinds() {
+        let lexed = [Lexed::Ident("a"), Lexed::Comma, Lexed::Ident("b")];
+        let mut input = SliceInput::new(&lexed);
+
+        assert!(!input.match_kind(&1));
+        assert!(input.match_kind(&0));
+        assert!(input.match_any());
+        assert!(input.match_kind(&0));
+        assert!(!input.match_any());
+        assert!(input.at_end());
+        assert_eq!(input.line_col(3), (1, 4));
+    }
+
+    #[test]
+    fn match_slice() {
+        let lexed = [Lexed::Ident("a"), Lexed::Ident("a"), Lexed::Ident("b")];
+        let mut input = SliceInput::new(&lexed);
+
+        input.set_pos(1);
+
+        assert!(input.match_slice(0, 1));
+        assert!(!input.match_slice(0, 1));
+        assert!(!input.match_slice(0, 2));
+        assert_eq!(input.pos(), 2);
+    }
+}
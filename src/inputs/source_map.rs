@@ -0,0 +1,238 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::{Arc, OnceLock};
+
+use super::super::Token;
+use super::line_index::{Columns, LineIndex};
+use super::{EmbeddedInput, OwnedInput, StringInput};
+
+/// A `struct` identifying a file added to a [`SourceMap`](struct.SourceMap).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SourceId(usize);
+
+impl SourceId {
+    /// Returns the index of the file in the order in which it was added, starting at 0.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+struct SourceFile {
+    name: String,
+    text: Arc<str>,
+    start: usize,
+    lines: OnceLock<LineIndex>
+}
+
+/// A `struct` that keeps track of multiple files, e.g. a file and the files it includes, and gives
+/// each of them a range of global positions that does not overlap with the others.
+///
+/// Parsing a file through [`input`](#method.input) or [`owned_input`](#method.owned_input) makes
+/// tokens and [`expected`](trait.Parser#tymethod.expected) positions global, so that they can
+/// be resolved back to a file name, line, and column without any other bookkeeping.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # use pest::SourceMap;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         file = { word* ~ eoi }
+///         word = @{ ['a'..'z']+ }
+///
+///         whitespace = _{ [" "] | ["\n"] }
+///     }
+/// }
+///
+/// let mut sources = SourceMap::new();
+///
+/// let main = sources.add("main.txt", "a b");
+/// let lib = sources.add("lib.txt", "c\nd!");
+///
+/// let mut parser = Rdp::new(sources.input(main));
+///
+/// assert!(parser.file());
+/// assert_eq!(sources.resolve_token(&parser.queue()[2]), Some(("main.txt", 1, 3)));
+///
+/// let mut parser = Rdp::new(sources.input(lib));
+///
+/// assert!(!parser.file());
+///
+/// let (_, pos) = parser.expected();
+///
+/// assert_eq!(sources.source(pos), Some(lib));
+/// assert_eq!(sources.resolve(pos), Some(("lib.txt", 2, 2)));
+/// # }
+/// ```
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>
+}
+
+impl SourceMap {
+    /// Creates a new empty `SourceMap`.
+    pub fn new() -> SourceMap {
+        SourceMap {
+            files: vec![]
+        }
+    }
+
+    /// Adds a file with a `name` and a `text` and returns its `SourceId`. Its global positions
+    /// start right after the end of the previously added file.
+    pub fn add<N: Into<String>, T: Into<Arc<str>>>(&mut self, name: N, text: T) -> SourceId {
+        // leaves room for the end position of the previous file
+        let start = match self.files.last() {
+            Some(file) => file.start + file.text.len() + 1,
+            None => 0
+        };
+
+        self.files.push(SourceFile {
+            name: name.into(),
+            text: text.into(),
+            start,
+            lines: OnceLock::new()
+        });
+
+        SourceId(self.files.len() - 1)
+    }
+
+    /// Returns the number of files in a `SourceMap`.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns whether a `SourceMap` has no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the name of a file.
+    pub fn name(&self, id: SourceId) -> &str {
+        &self.files[id.0].name
+    }
+
+    /// Returns the text of a file.
+    pub fn text(&self, id: SourceId) -> &str {
+        &self.files[id.0].text
+    }
+
+    /// Returns the global position where a file starts.
+    pub fn start(&self, id: SourceId) -> usize {
+        self.files[id.0].start
+    }
+
+    /// Returns an `Input` over a file that borrows the `SourceMap` and uses global positions.
+    pub fn input(&self, id: SourceId) -> EmbeddedInput<StringInput<'_>, SourceId> {
+        let file = &self.files[id.0];
+
+        EmbeddedInput::new(StringInput::new(&file.text), id, file.start, 1, 1)
+    }
+
+    /// Returns an `Input` over a file that shares its text and uses global positions. Since it
+    /// does not borrow the `SourceMap`, more files can be added while parsing, e.g. when
+    /// following includes.
+    pub fn owned_input(&self, id: SourceId) -> EmbeddedInput<OwnedInput, SourceId> {
+        let file = &self.files[id.0];
+
+        EmbeddedInput::new(OwnedInput::new(file.text.clone()), id, file.start, 1, 1)
+    }
+
+    /// Returns the file that contains the global position `pos`, or `None` if there is no such
+    /// file. The position right after the end of a file belongs to that file.
+    pub fn source(&self, pos: usize) -> Option<SourceId> {
+        let i = match self.files.binary_search_by_key(&pos, |file| file.start) {
+            Ok(i) => i,
+            Err(0) => return None,
+            Err(i) => i - 1
+        };
+
+        let file = &self.files[i];
+
+        if pos - file.start <= file.text.len() {
+            Some(SourceId(i))
+        } else {
+            None
+        }
+    }
+
+    /// Resolves the global position `pos` to the name of its file together with its 1-based line
+    /// and column, counted in `char`s, or returns `None` if no file contains it or if it is not
+    /// on a `char` boundary.
+    pub fn resolve(&self, pos: usize) -> Option<(&str, usize, usize)> {
+        let id = self.source(pos)?;
+        let file = &self.files[id.0];
+        let bytes = file.text.as_bytes();
+        let pos = pos - file.start;
+
+        if !file.text.is_char_boundary(pos) {
+            return None
+        }
+
+        let lines = file.lines.get_or_init(|| LineIndex::new(bytes));
+        let (line, start) = lines.line_start(bytes.len(), pos);
+
+        Some((&file.name, line, Columns::Chars.count(&file.text[start..pos]) + 1))
+    }
+
+    /// Resolves the start of `token` like [`resolve`](#method.resolve).
+    pub fn resolve_token<Rule>(&self, token: &Token<Rule>) -> Option<(&str, usize, usize)> {
+        self.resolve(token.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::Input;
+    use super::SourceMap;
+
+    #[test]
+    fn positions() {
+        let mut sources = SourceMap::new();
+
+        assert_eq!(sources.source(0), None);
+
+        let a = sources.add("a", "ab\nc");
+        let empty = sources.add(String::from("empty"), "");
+        let b = sources.add("b", String::from("嗨d"));
+
+        assert_eq!(sources.len(), 3);
+        assert_eq!((sources.start(a), sources.start(empty), sources.start(b)), (0, 5, 6));
+        assert_eq!(sources.source(4), Some(a));
+        assert_eq!(sources.source(5), Some(empty));
+        assert_eq!(sources.source(10), Some(b));
+        assert_eq!(sources.source(11), None);
+
+        assert_eq!(sources.resolve(3), Some(("a", 2, 1)));
+        assert_eq!(sources.resolve(5), Some(("empty", 1, 1)));
+        assert_eq!(sources.resolve(9), Some(("b", 1, 2)));
+        assert_eq!(sources.resolve(7), None);
+        assert_eq!(sources.resolve(8), None);
+    }
+
+    #[test]
+    fn inputs() {
+        let mut sources = SourceMap::new();
+
+        sources.add("a", "ab");
+
+        let b = sources.add("b", "cd");
+        let mut input = sources.owned_input(b);
+
+        assert!(input.match_string("c"));
+        assert_eq!(input.pos(), 4);
+        assert_eq!(*input.source(), b);
+
+        sources.add("c", "ef");
+
+        assert_eq!(input.slice(3, 5), "cd");
+        assert_eq!(sources.input(b).line_col(4), (1, 2));
+    }
+}
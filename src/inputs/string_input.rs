@@ -5,10 +5,11 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use std::ascii::AsciiExt;
-use std::str;
+use std::sync::OnceLock;
 
-use super::super::Input;
+use super::super::{Input, Trie};
+use super::chars;
+use super::line_index::{Columns, LineIndex};
 
 /// A `struct` useful for matching in-memory `String`s.
 ///
@@ -25,7 +26,9 @@ use super::super::Input;
 /// ```
 pub struct StringInput<'a> {
     string: &'a str,
-    pos: usize
+    pos: usize,
+    lines: OnceLock<LineIndex>,
+    columns: Columns
 }
 
 impl<'a> StringInput<'a> {
@@ -42,13 +45,98 @@ impl<'a> StringInput<'a> {
     /// ```
     pub fn new(string: &'a str) -> StringInput<'a> {
         StringInput {
-            string: string,
-            pos: 0
+            string,
+            pos: 0,
+            lines: OnceLock::new(),
+            columns: Columns::Chars
         }
     }
+
+    /// Returns the unit in which [`line_col`](trait.Input#tymethod.line_col) and
+    /// [`pos_at`](#method.pos_at) count columns.
+    pub fn columns(&self) -> Columns {
+        self.columns
+    }
+
+    /// Sets the unit in which [`line_col`](trait.Input#tymethod.line_col) and
+    /// [`pos_at`](#method.pos_at) count columns. Defaults to `Columns::Chars`.
+    pub fn set_columns(&mut self, columns: Columns) {
+        self.columns = columns;
+    }
+
+    /// Returns the line and column of a position, counting columns in `columns` regardless of
+    /// the current [setting](#method.set_columns).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Columns;
+    /// # use pest::StringInput;
+    /// let input = StringInput::new("嗨\nab");
+    ///
+    /// assert_eq!(input.line_col_in(3, Columns::Chars), (1, 2));
+    /// assert_eq!(input.line_col_in(3, Columns::Bytes), (1, 4));
+    /// ```
+    pub fn line_col_in(&self, pos: usize, columns: Columns) -> (usize, usize) {
+        let (line, start) = self.line_index().line_start(self.string.len(), pos);
+
+        (line, columns.count(&self.string[start..pos]) + 1)
+    }
+
+    /// Returns the position of a 1-based `line` and `col`, or `None` if the line does not exist or
+    /// is shorter than `col`. Columns are counted like in
+    /// [`line_col`](trait.Input#tymethod.line_col) and may point right after the last `char`. A
+    /// column inside of a wide `char`, like a tab, gives the position of that `char`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::StringInput;
+    /// let input = StringInput::new("ab\n嗨c");
+    ///
+    /// assert_eq!(input.pos_at(2, 2), Some(6));
+    /// assert_eq!(input.pos_at(2, 3), Some(7));
+    /// assert_eq!(input.pos_at(2, 4), None);
+    /// ```
+    pub fn pos_at(&self, line: usize, col: usize) -> Option<usize> {
+        let (start, end) = self.line_index().bounds(self.string.len(), line)?;
+
+        if col == 0 {
+            return None;
+        }
+
+        self.columns.find(&self.string[start..end], col - 1).map(|i| start + i)
+    }
+
+    /// Returns the text of a 1-based `line` without its line ending, or `None` if the line does
+    /// not exist. Useful for printing error snippets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::StringInput;
+    /// let input = StringInput::new("ab\r\ncd");
+    ///
+    /// assert_eq!(input.line(1), Some("ab"));
+    /// assert_eq!(input.line(2), Some("cd"));
+    /// assert_eq!(input.line(3), None);
+    /// ```
+    pub fn line(&self, line: usize) -> Option<&'a str> {
+        let (start, end) = self.line_index().bounds(self.string.len(), line)?;
+
+        Some(&self.string[start..end])
+    }
+
+    // The line index is only built on the first position query.
+    #[inline]
+    fn line_index(&self) -> &LineIndex {
+        self.lines.get_or_init(|| LineIndex::new(self.string.as_bytes()))
+    }
 }
 
 impl<'a> Input<'a> for StringInput<'a> {
+    type Slice = &'a str;
+
     #[inline]
     fn len(&self) -> usize {
         self.string.len()
@@ -59,6 +147,11 @@ impl<'a> Input<'a> for StringInput<'a> {
         self.string.is_empty()
     }
 
+    #[inline]
+    fn at_end(&self) -> bool {
+        self.pos == self.string.len()
+    }
+
     #[inline]
     fn pos(&self) -> usize {
         self.pos
@@ -76,47 +169,7 @@ impl<'a> Input<'a> for StringInput<'a> {
 
     #[inline]
     fn line_col(&self, pos: usize) -> (usize, usize) {
-        if pos > self.string.len() {
-            panic!("position out of bounds");
-        }
-
-        let mut pos = pos;
-        let slice = &self.string[..pos];
-        let mut chars = slice.chars().peekable();
-
-        let mut line_col = (1, 1);
-
-        while pos != 0 {
-            match chars.next() {
-                Some('\r') => {
-                    if let Some(&'\n') = chars.peek() {
-                        chars.next();
-
-                        if pos == 1 {
-                            pos -= 1;
-                            line_col = (line_col.0 + 1, 1);
-                        } else {
-                            pos -= 2;
-                            line_col = (line_col.0 + 1, 1);
-                        }
-                    } else {
-                        pos -= 1;
-                        line_col = (line_col.0 + 1, 1);
-                    }
-                }
-                Some('\n') => {
-                    pos -= 1;
-                    line_col = (line_col.0 + 1, 1);
-                },
-                Some(c) => {
-                    pos -= c.len_utf8();
-                    line_col = (line_col.0, line_col.1 + 1);
-                },
-                None => unreachable!(),
-            }
-        }
-
-        line_col
+        self.line_col_in(pos, self.columns)
     }
 
     #[inline]
@@ -124,8 +177,8 @@ impl<'a> Input<'a> for StringInput<'a> {
         let to = self.pos + string.len();
 
         if to <= self.string.len() {
-            let slice = unsafe { self.string.slice_unchecked(self.pos, to) };
-            let result = slice == string;
+            let slice = &self.string.as_bytes()[self.pos..to];
+            let result = slice == string.as_bytes();
 
             if result {
                 self.pos = to;
@@ -139,14 +192,24 @@ impl<'a> Input<'a> for StringInput<'a> {
 
     #[inline]
     fn match_insensitive(&mut self, string: &str) -> bool {
-        let slice = unsafe { self.string.slice_unchecked(self.pos, self.string.len()) };
+        let bytes = &self.string.as_bytes()[self.pos..];
 
-        if slice.is_char_boundary(string.len()) {
-            let slice = unsafe { slice.slice_unchecked(0, string.len()) };
-            let result = slice.eq_ignore_ascii_case(string);
+        if let Some(len) = chars::match_insensitive(bytes, string) {
+            self.pos += len;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_range(&mut self, left: char, right: char) -> bool {
+        if let Some(c) = self.string[self.pos..].chars().next() {
+            let result = left <= c && c <= right;
 
             if result {
-                self.pos += string.len();
+                self.pos += c.len_utf8();
             }
 
             result
@@ -156,29 +219,96 @@ impl<'a> Input<'a> for StringInput<'a> {
     }
 
     #[inline]
-    fn match_range(&mut self, left: char, right: char) -> bool {
-        let len = left.len_utf8();
+    fn match_char_by<F>(&mut self, predicate: F) -> bool where F: FnOnce(char) -> bool {
+        if let Some(c) = self.string[self.pos..].chars().next() {
+            let result = predicate(c);
 
-        if len != right.len_utf8() {
-            panic!("ranges should have same-sized UTF-8 limits");
+            if result {
+                self.pos += c.len_utf8();
+            }
+
+            result
+        } else {
+            false
         }
+    }
 
-        let to = self.pos + len;
+    #[inline]
+    fn match_any(&mut self) -> bool {
+        if let Some(c) = self.string[self.pos..].chars().next() {
+            self.pos += c.len_utf8();
 
-        if to <= self.string.len() {
-            if let Ok(string) = str::from_utf8(&self.string.as_bytes()[self.pos..to]) {
-                let c = string.chars().next().unwrap();
+            true
+        } else {
+            false
+        }
+    }
 
-                let result = left <= c && c <= right;
+    #[inline]
+    fn match_bytes(&mut self, bytes: &[u8]) -> bool {
+        let to = self.pos + bytes.len();
 
-                if result {
-                    self.pos += len;
-                }
+        if to <= self.string.len() && self.string.is_char_boundary(to) {
+            let result = &self.string.as_bytes()[self.pos..to] == bytes;
 
-                result
-            } else {
-                false
+            if result {
+                self.pos = to;
             }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_byte_range(&mut self, left: u8, right: u8) -> bool {
+        let to = self.pos + 1;
+
+        if to <= self.string.len() && self.string.is_char_boundary(to) {
+            let byte = self.string.as_bytes()[self.pos];
+            let result = left <= byte && byte <= right;
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_slice(&mut self, start: usize, end: usize) -> bool {
+        let to = self.pos + (end - start);
+
+        if to <= self.string.len() {
+            let bytes = self.string.as_bytes();
+            let result = bytes[start..end] == bytes[self.pos..to];
+
+            if result {
+                self.pos = to;
+            }
+
+            result
+        } else {
+            false
+        }
+    }
+
+    #[inline]
+    fn match_trie(&mut self, trie: &Trie) -> bool {
+        let string = self.string;
+        let pos = self.pos;
+        let len = trie.matches(&string.as_bytes()[pos..])
+                      .filter(|&len| string.is_char_boundary(pos + len))
+                      .last();
+
+        if let Some(len) = len {
+            self.pos += len;
+
+            true
         } else {
             false
         }
@@ -187,7 +317,7 @@ impl<'a> Input<'a> for StringInput<'a> {
 
 #[cfg(test)]
 mod tests {
-    use super::super::super::Input;
+    use super::super::super::{Input, Trie};
     use super::StringInput;
 
     #[test]
@@ -253,6 +383,37 @@ mod tests {
         assert_eq!(input.line_col(11), (4, 3));
     }
 
+    #[test]
+    fn pos_at() {
+        let input = StringInput::new("a\rb\nc\r\nd嗨");
+
+        assert_eq!(input.pos_at(0, 1), None);
+        assert_eq!(input.pos_at(1, 1), Some(0));
+        assert_eq!(input.pos_at(1, 2), Some(1));
+        assert_eq!(input.pos_at(1, 3), None);
+        assert_eq!(input.pos_at(3, 2), Some(5));
+        assert_eq!(input.pos_at(4, 1), Some(7));
+        assert_eq!(input.pos_at(4, 3), Some(11));
+        assert_eq!(input.pos_at(5, 1), None);
+
+        for pos in vec![0, 1, 2, 3, 4, 5, 7, 8, 11] {
+            let (line, col) = input.line_col(pos);
+
+            assert_eq!(input.pos_at(line, col), Some(pos));
+        }
+    }
+
+    #[test]
+    fn line() {
+        let input = StringInput::new("a\rb\nc\r\nd嗨\n");
+
+        assert_eq!(input.line(1), Some("a"));
+        assert_eq!(input.line(3), Some("c"));
+        assert_eq!(input.line(4), Some("d嗨"));
+        assert_eq!(input.line(5), Some(""));
+        assert_eq!(input.line(6), None);
+    }
+
     #[test]
     fn match_range() {
         let mut input = StringInput::new("bbbb");
@@ -273,5 +434,68 @@ mod tests {
         assert!(input.match_insensitive("aaa"));
 
         assert_eq!(input.pos(), 7);
+
+        let mut input = StringInput::new("ΣΊΣΥΦΟΣ \u{212a}ELVIN");
+
+        assert!(input.match_insensitive("σίσυφος k"));
+        assert!(!input.match_insensitive("elvis"));
+        assert!(input.match_insensitive("elvin"));
+        assert!(input.at_end());
+    }
+
+    #[test]
+    fn match_range_mixed_lengths() {
+        let mut input = StringInput::new("a嗨𝄞");
+
+        assert!(input.match_range('a', '\u{10ffff}'));
+        assert!(!input.match_range('\0', '\u{7f}'));
+        assert!(input.match_range('\u{80}', '\u{ffff}'));
+        assert!(!input.match_range('\0', '\u{ffff}'));
+        assert!(input.match_range('\u{ffff}', '\u{10ffff}'));
+        assert!(!input.match_range('\0', '\u{10ffff}'));
+    }
+
+    #[test]
+    fn match_any() {
+        let mut input = StringInput::new("嗨a");
+
+        assert!(input.match_any());
+        assert_eq!(input.pos(), 3);
+        assert!(input.match_any());
+        assert!(!input.match_any());
+        assert_eq!(input.pos(), 4);
+    }
+
+    #[test]
+    fn match_bytes() {
+        let mut input = StringInput::new("ab嗨");
+
+        assert!(input.match_bytes(b"ab"));
+        assert!(!input.match_bytes(&[0xe5]));
+        assert!(!input.match_byte_range(0x00, 0xff));
+
+        assert_eq!(input.pos(), 2);
+    }
+
+    #[test]
+    fn match_trie() {
+        let trie = Trie::new(&["a".as_bytes(), b"ab", b"ab\xe5"]);
+        let mut input = StringInput::new("ab嗨");
+
+        assert!(input.match_trie(&trie));
+        assert_eq!(input.pos(), 2);
+        assert!(!input.match_trie(&trie));
+    }
+
+    #[test]
+    fn match_slice() {
+        let mut input = StringInput::new("abcab");
+
+        input.set_pos(3);
+
+        assert!(input.match_slice(0, 2));
+        assert!(!input.match_slice(0, 1));
+
+        assert_eq!(input.pos(), 5);
     }
 }
@@ -33,8 +33,20 @@
 //! assert_eq!(input.pos(), 4);                  // last match advances the parser by 1
 //! ```
 //!
-//! `Input` is also supposed to return a `&str` `slice` of its input by calling
-//! [`Input::slice`](trait.Input#tymethod.slice).
+//! `Input` is also supposed to return a `slice` of its input by calling
+//! [`Input::slice`](trait.Input#tymethod.slice). This is a `&str` for `StringInput`, a `&[u8]`
+//! for `BytesInput`, which matches raw bytes that are not necessarily valid UTF-8, a `String`
+//! for `ReaderInput`, which streams its input from any `io::Read`, and a `SharedStr` for
+//! `OwnedInput`, which owns its text so that parsers built on it can outlive the source or be
+//! sent to other threads. `SliceInput` parses a `&[T]` of elements instead of text, e.g. the
+//! output of a separate lexer, and its slices are `&[T]`. `ChunkedInput` matches text split in
+//! chunks, like the buffer of an editor, and its slices are `Cow<str>`. Any of them can be
+//! wrapped in an `EmbeddedInput` when it holds a fragment of a larger file, so that positions
+//! and lines refer to the enclosing file. `NormalizedInput` hides byte order marks, `"\r\n"`
+//! line endings, and line continuations from the grammar while mapping positions back to the
+//! original text, while `DecodedInput` decodes UTF-16 or Windows-1252 bytes and maps positions
+//! back to the original bytes. With the `mmap` feature, a `MappedFile` maps a file into memory and
+//! parses it through a `StringInput` without reading it into a `String` first.
 //!
 //! `Parser` gets constructed on top of an `Input` and delegates position access to
 //! [`Parser::pos`](trait.Parser#tymethod.pos) and
@@ -524,6 +536,9 @@
 //! # }
 //! ```
 
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+
 #[macro_use]
 mod grammar;
 #[macro_use]
@@ -533,10 +548,17 @@ mod parsers;
 mod input;
 mod inputs;
 mod parser;
+mod trie;
 
 pub mod prelude;
+pub mod unicode;
 
-pub use input::Input;
-pub use inputs::StringInput;
+pub use input::{Input, Literal, RangeLimit};
+#[cfg(feature = "mmap")]
+pub use inputs::MappedFile;
+pub use inputs::{BytesInput, ChunkedInput, Columns, DecodedInput, EmbeddedInput, Encoding,
+                 KindInput, Lexeme, Normalization, NormalizedInput, OwnedInput, ReaderInput,
+                 SharedStr, SliceInput, SourceId, SourceMap, StringInput};
 pub use parser::Parser;
-pub use parsers::Token;
+pub use parsers::{Snapshot, Token};
+pub use trie::{Matches, Trie};
@@ -5,7 +5,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use super::Input;
+use super::{Input, Snapshot};
 
 /// A `trait` that defines a parser.
 pub trait Parser<'a, T: Input<'a>> {
@@ -32,7 +32,7 @@ pub trait Parser<'a, T: Input<'a>> {
     fn queue_mut(&mut self) -> &mut Vec<Self::Token>;
 
     /// Returns the queue of all matched `(Token, value)`s.
-    fn queue_with_captures(&self) -> Vec<(Self::Token, String)>;
+    fn queue_with_captures(&self) -> Vec<(Self::Token, T::Slice)>;
 
     /// Returns the current index within the queue. Used in `process!`.
     fn queue_index(&self) -> usize;
@@ -46,25 +46,63 @@ pub trait Parser<'a, T: Input<'a>> {
     /// Skips whitespace and comments.
     fn skip(&mut self);
 
+    /// Returns whether a `Parser` failed after a `[cut()]`, in which case no other alternatives
+    /// were tried and [`expected`](trait.Parser#tymethod.expected) reports the failure after the
+    /// cut.
+    fn cut_failed(&self) -> bool;
+
     /// Returns whether a `Parser` is currently inside an atomic rule.
     fn is_atomic(&self) -> bool;
 
     /// Sets a `Parser` to atomic rule mode, barring comment & white-space skipping.
     fn set_atomic(&mut self, value: bool);
 
+    /// Returns whether a `Parser` memoizes the results of all its rules.
+    fn is_packrat(&self) -> bool;
+
+    /// Sets a `Parser` to packrat mode, memoizing the result of every non-silent rule at every
+    /// position, instead of only those of rules marked with `#[memo]` in `grammar!`.
+    fn set_packrat(&mut self, value: bool);
+
     /// Keeps track of rule failures. It gets called when a `Rule` fails at `pos`.
     fn track(&mut self, failed: Self::Rule, pos: usize);
 
-    /// Returns the length of the tracked `Rule`s.
+    /// Returns the number of tracked `Rule`s and labels, and their position.
     fn tracked_len_pos(&self) -> (usize, usize);
 
     /// Retuns a `Vec` of all expected `Rule`s at the deepest position where the parsing last
     /// stopped. It only returns leafs from the rule tree. Used for error reporting.
     fn expected(&mut self) -> (Vec<Self::Rule>, usize);
 
-    /// Returns the stack `Vec`.
-    fn stack(&self) -> &Vec<String>;
-
-    /// Returns the mutable stack `Vec`.
-    fn stack_mut(&mut self) -> &mut Vec<String>;
+    /// Returns the labels given with `#[label(...)]` in `grammar!` that were expected at the same
+    /// position as the `Rule`s returned by [`expected`](trait.Parser#tymethod.expected), in the
+    /// order they were tracked. A label replaces the failures of the expression it labels.
+    fn expected_labels(&self) -> (Vec<&'static str>, usize);
+
+    /// Returns the errors that were recovered from with `[recover(rule, until)]`, in the order of
+    /// their `Rule::error` `Token`s in the queue. Each error holds the expected `Rule`s, the
+    /// expected labels and their position, like [`expected`](trait.Parser#tymethod.expected) and
+    /// [`expected_labels`](trait.Parser#tymethod.expected_labels).
+    #[allow(clippy::type_complexity)]
+    fn errors(&self) -> Vec<(Vec<Self::Rule>, Vec<&'static str>, usize)>;
+
+    /// Returns the stack `Vec` of captured `(start, end)` positions. It can only be changed with
+    /// [`stack_push`](trait.Parser#tymethod.stack_push) and
+    /// [`stack_pop`](trait.Parser#tymethod.stack_pop), so that every change is undone on
+    /// backtracking.
+    fn stack(&self) -> &Vec<(usize, usize)>;
+
+    /// Pushes a captured `(start, end)` position onto the stack.
+    fn stack_push(&mut self, capture: (usize, usize));
+
+    /// Pops a captured `(start, end)` position from the stack, returning `None` if it is empty.
+    fn stack_pop(&mut self) -> Option<(usize, usize)>;
+
+    /// Returns a [`Snapshot`](struct.Snapshot) of the current position, queue length, stack,
+    /// end-of-input flag and atomic mode of a `Parser`.
+    fn snapshot(&self) -> Snapshot;
+
+    /// Restores a `Parser` to a [`Snapshot`](struct.Snapshot) taken earlier, undoing every change
+    /// made to the stack since.
+    fn restore(&mut self, snapshot: Snapshot);
 }
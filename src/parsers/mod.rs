@@ -8,6 +8,8 @@
 #[macro_use]
 mod rdp;
 
+mod snapshot;
 mod token;
 
+pub use self::snapshot::Snapshot;
 pub use self::token::Token;
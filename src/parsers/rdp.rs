@@ -12,8 +12,48 @@
 ///
 /// It also implements an `enum` called `Rule` that has a value for all
 /// [non-silent](macro.grammar!#silent-rules-_) rules, but also for
-/// [`any` and `eoi`](macro.grammar!). These `Rule`s are used within `Token`s to specify the type
-/// of rule that matched.
+/// [`any`, `soi`, `eoi`](macro.grammar!), the `pop`, `peek` and `drop`
+/// [stack operations](macro.grammar!#syntax), and `error` for the `Token`s of
+/// [error recovery](macro.grammar!#error-recovery).
+/// These `Rule`s are used within `Token`s to specify the type of rule that matched.
+///
+/// # Slice
+///
+/// By default, the generated parser works with any `Input` whose
+/// [`Slice`](trait.Input#associatedtype.Slice) is `&'input str`, so captures in `process!` are
+/// `&str`s. A different slice type can be selected by starting the `macro` with a `slice:` line,
+/// e.g. `&'input [u8]` for parsing a [`BytesInput`](struct.BytesInput).
+///
+/// Parsers of a [`SliceInput`](struct.SliceInput) that match elements with `[kind(...)]` also
+/// need a `kind:` line right after the `slice:` line, which selects the
+/// [`Kind`](trait.KindInput#associatedtype.Kind) of the `Input`. Matching kinds of any other type
+/// does not compile.
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     slice: &'input [u8];
+///
+///     grammar! {
+///         header = { [b"\x89PNG"] ~ [b'\x00'..b'\x7f']* }
+///     }
+///
+///     process! {
+///         magic(&self) -> &'input [u8] {
+///             (&header: header) => &header[..4]
+///         }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(BytesInput::new(b"\x89PNG\r\n"));
+///
+/// assert!(parser.header());
+/// assert!(parser.end());
+/// assert_eq!(parser.magic(), b"\x89PNG");
+/// # }
+/// ```
 ///
 /// # Examples
 ///
@@ -38,12 +78,16 @@
 macro_rules! impl_rdp {
     // implement rules
     ( @rules $( $name:ident )* ) => {
-        #[allow(dead_code, non_camel_case_types)]
+        #[allow(dead_code, non_camel_case_types, clippy::upper_case_acronyms)]
         #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
         pub enum Rule {
             any,
             soi,
             eoi,
+            pop,
+            peek,
+            drop,
+            error,
             $( $name ),*
         }
     };
@@ -52,6 +96,9 @@ macro_rules! impl_rdp {
     ( @filter [  ] [ $( $rules:tt )* ] ) => {
         impl_rdp!(@rules $( $rules )*);
     };
+    ( @filter [ # [ $( $_marker:tt )* ] $( $tail:tt )* ] $rules:tt ) => {
+        impl_rdp!(@filter [ $( $tail )* ] $rules);
+    };
     ( @filter [ $name:ident = { { $( $_primary:tt )* } $( $ts:tt )* } $( $tail:tt )* ]
       [ $( $rules:tt )* ] ) => {
         impl_rdp!(@filter [ $( $tail )* $( $ts )* ] [ $name $( $rules )* ]);
@@ -77,6 +124,9 @@ macro_rules! impl_rdp {
     ( @filter [ $name:ident = !@{ $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ] ) => {
         impl_rdp!(@filter [ $( $tail )* ] [ $name $( $rules )* ]);
     };
+    ( @filter [ $name:ident = k@{ $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ] ) => {
+        impl_rdp!(@filter [ $( $tail )* ] [ $name $( $rules )* ]);
+    };
     ( @filter [ $_name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ] [ $( $rules:tt )* ] ) => {
         impl_rdp!(@filter [ $( $tail )* ] [ $( $rules )* ]);
     };
@@ -90,6 +140,9 @@ macro_rules! impl_rdp {
         }
     };
     ( @ws whitespace = $( $_ts:tt )* ) => ();
+    ( @ws # [ $( $_marker:tt )* ] $( $tail:tt )* ) => {
+        impl_rdp!(@ws $( $tail )*);
+    };
     ( @ws $_name:ident = { $( $_ts:tt )* } $( $tail:tt )* ) => {
         impl_rdp!(@ws $( $tail )*);
     };
@@ -99,6 +152,9 @@ macro_rules! impl_rdp {
     ( @ws $_name:ident = !@{ $( $_ts:tt )* } $( $tail:tt )* ) => {
         impl_rdp!(@ws $( $tail )*);
     };
+    ( @ws $_name:ident = k@{ $( $_ts:tt )* } $( $tail:tt )* ) => {
+        impl_rdp!(@ws $( $tail )*);
+    };
     ( @ws $_name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ) => {
         impl_rdp!(@ws $( $tail )*);
     };
@@ -112,6 +168,9 @@ macro_rules! impl_rdp {
         }
     };
     ( @com comment = $( $_ts:tt )* ) => ();
+    ( @com # [ $( $_marker:tt )* ] $( $tail:tt )* ) => {
+        impl_rdp!(@com $( $tail )*);
+    };
     ( @com $_name:ident = { $( $_ts:tt )* } $( $tail:tt )* ) => {
         impl_rdp!(@com $( $tail )*);
     };
@@ -121,35 +180,86 @@ macro_rules! impl_rdp {
     ( @com $_name:ident = !@{ $( $_ts:tt )* } $( $tail:tt )* ) => {
         impl_rdp!(@com $( $tail )*);
     };
+    ( @com $_name:ident = k@{ $( $_ts:tt )* } $( $tail:tt )* ) => {
+        impl_rdp!(@com $( $tail )*);
+    };
     ( @com $_name:ident = _{ $( $_ts:tt )* } $( $tail:tt )* ) => {
         impl_rdp!(@com $( $tail )*);
     };
 
     ( grammar! { $( $ts:tt )* } $( $mac:ident! { $( $rest:tt )* } )* ) => {
+        impl_rdp! {
+            slice: &'input str;
+
+            grammar! {
+                $( $ts )*
+            }
+
+            $(
+                $mac! {
+                    $( $rest )*
+                }
+            )*
+        }
+    };
+
+    ( slice: $slice:ty; $( kind: $kind:ty; )? grammar! { $( $ts:tt )* }
+      $( $mac:ident! { $( $rest:tt )* } )* ) => {
         pub struct Rdp<T> {
             input:       T,
             queue:       Vec<Token<Rule>>,
             queue_index: ::std::cell::Cell<usize>,
             failures:    Vec<Rule>,
+            labels:      Vec<&'static str>,
             fail_pos:    usize,
-            stack:       Vec<String>,
+            stack:       Vec<(usize, usize)>,
+            stack_ops:   Vec<Option<(usize, usize)>>,
             atomic:      bool,
-            eoi_matched: bool
+            eoi_matched: bool,
+            calls:       Vec<(&'static str, usize,
+                              Option<(usize, Vec<Token<Rule>>,
+                                      Vec<(usize, (Vec<Rule>, Vec<&'static str>, usize))>)>,
+                              bool)>,
+            seed_hits:   usize,
+            packrat:     bool,
+            stack_uses:  ::std::cell::Cell<usize>,
+            holds:       Vec<usize>,
+            memo:        ::std::collections::HashMap<(Rule, usize, bool),
+                                                     (Option<(usize, Vec<Token<Rule>>, bool)>,
+                                                      (Vec<Rule>, Vec<&'static str>, usize))>,
+            cut:         Option<(usize, (Vec<Rule>, Vec<&'static str>, usize))>,
+            cut_failed:  bool,
+            errors:      Vec<(usize, (Vec<Rule>, Vec<&'static str>, usize))>
         }
 
         impl_rdp!(@filter [ $( $ts )* ] []);
 
-        impl<'input, T: $crate::Input<'input>> Rdp<T> {
+        // character classes of grammar! check ranges explicitly
+        #[allow(clippy::manual_is_ascii_check)]
+        impl<'input, T: $crate::Input<'input, Slice = $slice>> Rdp<T>
+            $( where T: $crate::KindInput<'input, Kind = $kind> )? {
+
             pub fn new(input: T) -> Rdp<T> {
                 Rdp {
-                    input:       input,
+                    input,
                     queue:       vec![],
                     queue_index: ::std::cell::Cell::new(0),
                     failures:    vec![],
+                    labels:      vec![],
                     fail_pos:    0,
                     stack:       vec![],
+                    stack_ops:   vec![],
                     atomic:      false,
-                    eoi_matched: false
+                    eoi_matched: false,
+                    calls:       vec![],
+                    seed_hits:   0,
+                    packrat:     false,
+                    stack_uses:  ::std::cell::Cell::new(0),
+                    holds:       vec![],
+                    memo:        ::std::collections::HashMap::new(),
+                    cut:         None,
+                    cut_failed:  false,
+                    errors:      vec![]
                 }
             }
 
@@ -159,24 +269,21 @@ macro_rules! impl_rdp {
             #[allow(dead_code)]
             #[inline]
             pub fn any(&mut self) -> bool {
-                if self.end() {
+                if self.input.match_any() {
+                    true
+                } else {
                     let pos = self.input.pos();
 
                     self.track(Rule::any, pos);
 
                     false
-                } else {
-                    let next = self.input.pos() + 1;
-                    self.input.set_pos(next);
-
-                    true
                 }
             }
 
             #[allow(dead_code)]
             #[inline]
             pub fn soi(&mut self) -> bool {
-                let result = self.input.pos() == 0;
+                let result = self.input.pos() == self.input.start_pos();
 
                 if !result {
                     let pos = self.input.pos();
@@ -203,6 +310,409 @@ macro_rules! impl_rdp {
                 result
             }
 
+            // Calls `rule` named `name` at the current position. If `left_recursive` is set, a
+            // left-recursive call of the same rule at the same position matches the seed of the
+            // outer call instead, which starts out as a failure and is grown by calling `rule`
+            // again for as long as it matches further than before.
+            #[allow(dead_code)]
+            fn __recurse<F>(&mut self, name: &'static str, left_recursive: bool,
+                            mut rule: F) -> bool
+                where F: FnMut(&mut Self) -> bool {
+
+                if !left_recursive {
+                    return self.__cut_scope(rule) && !self.cut_failed
+                }
+
+                let pos = self.input.pos();
+
+                for call in self.calls.iter_mut().rev() {
+                    if call.1 < pos {
+                        break
+                    }
+
+                    if call.0 == name {
+                        call.3 = true;
+                        self.seed_hits += 1;
+
+                        return match call.2 {
+                            Some((end, ref tokens, ref errors)) => {
+                                self.queue.extend_from_slice(tokens);
+                                self.errors.extend_from_slice(errors);
+                                self.input.set_pos(end);
+
+                                true
+                            },
+                            None => false
+                        }
+                    }
+                }
+
+                let snapshot = self.snapshot();
+                let index = self.calls.len();
+
+                self.__hold(snapshot.pos);
+                self.calls.push((name, pos, None, false));
+
+                let result = self.__cut_scope(&mut rule);
+
+                if result && self.calls[index].3 {
+                    loop {
+                        let end = self.input.pos();
+                        let tokens = self.queue.split_off(snapshot.queue_len);
+                        let errors = self.errors.split_off(snapshot.errors_len);
+
+                        self.calls[index].2 = Some((end, tokens, errors));
+                        self.restore(snapshot);
+
+                        if !self.__cut_scope(&mut rule) || self.input.pos() <= end {
+                            self.restore(snapshot);
+
+                            let (end, tokens, errors) = self.calls[index].2.take().unwrap();
+
+                            self.queue.extend(tokens);
+                            self.errors.extend(errors);
+                            self.input.set_pos(end);
+
+                            break
+                        }
+                    }
+                }
+
+                self.calls.pop();
+                self.__unhold();
+
+                result && !self.cut_failed
+            }
+
+            // Calls `rule` through the memo table when `memo` is set or the parser is in packrat
+            // mode. Results that depend on the seed of an unfinished left-recursive call, that
+            // used the stack or that recovered from errors are not memoized. The failures tracked
+            // by `rule` are memoized along with its result and tracked again on every hit.
+            #[allow(dead_code)]
+            fn __memoize<F>(&mut self, rule: Rule, memo: bool, f: F) -> bool
+                where F: FnOnce(&mut Self) -> bool {
+
+                if !memo && !self.packrat {
+                    return f(self)
+                }
+
+                let key = (rule, self.input.pos(), self.atomic);
+
+                if let Some(&(ref entry, ref tracked)) = self.memo.get(&key) {
+                    let tracked = tracked.clone();
+                    let result = match *entry {
+                        Some((end, ref tokens, eoi_matched)) => {
+                            self.queue.extend_from_slice(tokens);
+                            self.input.set_pos(end);
+                            self.eoi_matched |= eoi_matched;
+
+                            true
+                        },
+                        None => false
+                    };
+
+                    self.__merge_failures(tracked);
+
+                    return result
+                }
+
+                let len = self.queue.len();
+                let seed_hits = self.seed_hits;
+                let stack_uses = self.stack_uses.get();
+                let failures = self.__take_failures();
+
+                let result = f(self);
+
+                let tracked = self.__take_failures();
+
+                self.__merge_failures(failures);
+                self.__merge_failures(tracked.clone());
+
+                let recovered = result &&
+                                self.queue[len..].iter().any(|token| token.rule == Rule::error);
+
+                if self.seed_hits == seed_hits && self.stack_uses.get() == stack_uses &&
+                   !self.cut_failed && !recovered {
+                    let entry = if result {
+                        Some((self.input.pos(), self.queue[len..].to_vec(), self.eoi_matched))
+                    } else {
+                        None
+                    };
+
+                    self.memo.insert(key, (entry, tracked));
+                }
+
+                result
+            }
+
+            // Commits to the current alternative. Failures tracked so far are set aside, so that a
+            // failure after the cut is reported at its own position.
+            #[allow(dead_code)]
+            fn __cut(&mut self) -> bool {
+                if let Some((_, failures)) = self.cut.take() {
+                    self.__merge_failures(failures);
+                }
+
+                let pos = self.input.pos();
+
+                self.cut = Some((pos, self.__take_failures()));
+
+                true
+            }
+
+            // Calls `rule` in a new cut scope. If `rule` fails after a cut, the parser fails hard
+            // and every enclosing scope fails without trying any other alternative. A failure that
+            // tracked nothing after the cut is reported no earlier than where the cut committed.
+            #[allow(dead_code)]
+            #[inline]
+            fn __cut_scope<F>(&mut self, rule: F) -> bool
+                where F: FnOnce(&mut Self) -> bool {
+
+                if self.cut_failed {
+                    return false
+                }
+
+                let outer = self.cut.take();
+
+                let result = rule(self);
+
+                if let Some((pos, failures)) = ::std::mem::replace(&mut self.cut, outer) {
+                    let tracked = !self.failures.is_empty() || !self.labels.is_empty();
+
+                    if result || !tracked {
+                        self.__merge_failures(failures);
+                    }
+
+                    if !result {
+                        if !tracked && !self.atomic {
+                            self.__track_pos(pos);
+                        }
+
+                        self.cut_failed = true;
+                    }
+                }
+
+                result
+            }
+
+            // Calls `rule` and, if it fails without tracking any failure past the current position,
+            // tracks `label` instead of the failures at the current position.
+            #[allow(dead_code)]
+            fn __with_label<F>(&mut self, label: Option<&'static str>, rule: F) -> bool
+                where F: FnOnce(&mut Self) -> bool {
+
+                let label = match label {
+                    Some(label) => label,
+                    None => return rule(self)
+                };
+
+                let pos = self.input.pos();
+                let failures = self.__take_failures();
+
+                let result = rule(self);
+
+                if !result && !self.atomic && (self.failures.is_empty() && self.labels.is_empty() ||
+                                               self.fail_pos <= pos) {
+                    self.failures.clear();
+                    self.labels = vec![label];
+                    self.fail_pos = pos;
+                }
+
+                self.__merge_failures(failures);
+
+                result
+            }
+
+            // Matches `rule`, or records the `Rule`s it expected and skips input up to where
+            // `until` matches, queueing a `Rule::error` token over the skipped input. If `until`
+            // already matches, the token is empty. It fails if the input has ended or if it has
+            // just queued an empty token at the same position, so that repetitions end.
+            #[allow(dead_code)]
+            fn __recover<F, G>(&mut self, rule: F, mut until: G) -> bool
+                where F: FnOnce(&mut Self) -> bool,
+                      G: FnMut(&mut Self) -> bool {
+
+                if self.cut_failed {
+                    return false
+                }
+
+                let snapshot = self.snapshot();
+                let failures = self.__take_failures();
+
+                self.__hold(snapshot.pos);
+
+                if self.__cut_scope(|slf| slf.try(false, rule)) {
+                    self.__merge_failures(failures);
+                    self.__unhold();
+
+                    return true
+                }
+
+                let (mut expected, labels, mut expected_pos) = self.__take_failures();
+
+                if expected.is_empty() && labels.is_empty() {
+                    expected_pos = snapshot.pos;
+                }
+
+                self.__merge_failures(failures);
+                self.cut_failed = false;
+                self.restore(snapshot);
+                self.__unhold();
+
+                // skip atomically in order not to track the failures of `until`
+                self.atomic = true;
+
+                while !self.input.at_end() && !self.try(true, &mut until) {
+                    self.input.match_any();
+                }
+
+                self.atomic = snapshot.atomic;
+
+                let start = snapshot.pos;
+                let end = self.input.pos();
+
+                let repeated = match self.queue.last() {
+                    Some(token) => token.rule == Rule::error && token.start == start &&
+                                   token.end == start,
+                    None => false
+                };
+
+                if end == start && (self.input.at_end() || repeated) {
+                    self.__merge_failures((expected, labels, expected_pos));
+
+                    return false
+                }
+
+                expected.sort();
+                expected.dedup();
+
+                self.errors.push((start, (expected, Self::__unique_labels(&labels), expected_pos)));
+                self.queue.push(Token {
+                    rule: Rule::error,
+                    start,
+                    end
+                });
+
+                true
+            }
+
+            // Keeps the input from discarding the text from `pos` on until the matching `__unhold`,
+            // so that the parser can still rewind to `pos`. Only inputs that discard are tracked.
+            #[allow(dead_code)]
+            #[inline]
+            fn __hold(&mut self, pos: usize) {
+                if T::DISCARDS {
+                    self.holds.push(pos);
+                }
+            }
+
+            // Moves the latest hold forward to `pos`.
+            #[allow(dead_code)]
+            #[inline]
+            fn __move_hold(&mut self, pos: usize) {
+                if T::DISCARDS {
+                    if let Some(hold) = self.holds.last_mut() {
+                        *hold = pos;
+                    }
+
+                    self.__discard();
+                }
+            }
+
+            // Releases the latest hold.
+            #[allow(dead_code)]
+            #[inline]
+            fn __unhold(&mut self) {
+                if T::DISCARDS {
+                    self.holds.pop();
+                    self.__discard();
+                }
+            }
+
+            // Discards the input before the oldest position that is still held, queued, or
+            // captured on the stack.
+            fn __discard(&mut self) {
+                let mut pos = self.input.pos();
+
+                if let Some(&hold) = self.holds.first() {
+                    pos = ::std::cmp::min(pos, hold);
+                }
+
+                if let Some(token) = self.queue.first() {
+                    pos = ::std::cmp::min(pos, token.start);
+                }
+
+                if let Some(&(start, _)) = self.stack.first() {
+                    pos = ::std::cmp::min(pos, start);
+                }
+
+                self.input.discard(pos);
+            }
+
+            // Returns whether a failure at `pos` should be tracked, forgetting all failures tracked
+            // at lower positions.
+            fn __track_pos(&mut self, pos: usize) -> bool {
+                if self.failures.is_empty() && self.labels.is_empty() || pos > self.fail_pos {
+                    self.failures.clear();
+                    self.labels.clear();
+                    self.fail_pos = pos;
+
+                    true
+                } else {
+                    pos == self.fail_pos
+                }
+            }
+
+            fn __unique_labels(labels: &[&'static str]) -> Vec<&'static str> {
+                let mut unique = vec![];
+
+                for &label in labels {
+                    if !unique.contains(&label) {
+                        unique.push(label);
+                    }
+                }
+
+                unique
+            }
+
+            fn __take_failures(&mut self) -> (Vec<Rule>, Vec<&'static str>, usize) {
+                let failures = ::std::mem::take(&mut self.failures);
+                let labels = ::std::mem::take(&mut self.labels);
+
+                (failures, labels, self.fail_pos)
+            }
+
+            fn __merge_failures(&mut self, failures: (Vec<Rule>, Vec<&'static str>, usize)) {
+                let (failures, labels, fail_pos) = failures;
+
+                if failures.is_empty() && labels.is_empty() {
+                    return
+                }
+
+                if self.__track_pos(fail_pos) {
+                    self.failures.extend(failures);
+                    self.labels.extend(labels);
+                }
+            }
+
+            // Matches one `char` of the Unicode class `name`, which is tracked as a label when it
+            // fails.
+            #[allow(dead_code)]
+            #[inline]
+            fn __unicode(&mut self, predicate: fn(char) -> bool, name: &'static str) -> bool {
+                if self.input.match_char_by(predicate) {
+                    true
+                } else {
+                    let pos = self.input.pos();
+
+                    if !self.atomic && self.__track_pos(pos) {
+                        self.labels.push(name);
+                    }
+
+                    false
+                }
+            }
+
             grammar! {
                 $( $ts )*
             }
@@ -214,7 +724,9 @@ macro_rules! impl_rdp {
             )*
         }
 
-        impl<'input, T: $crate::Input<'input>> $crate::Parser<'input, T> for Rdp<T> {
+        impl<'input, T: $crate::Input<'input, Slice = $slice>> $crate::Parser<'input, T> for Rdp<T>
+            $( where T: $crate::KindInput<'input, Kind = $kind> )? {
+
             type Rule = Rule;
             type Token = Token<Rule>;
 
@@ -230,7 +742,7 @@ macro_rules! impl_rdp {
 
             #[inline]
             fn end(&self) -> bool {
-                self.input.len() == self.input.pos()
+                self.input.at_end()
             }
 
             #[inline]
@@ -240,11 +752,23 @@ macro_rules! impl_rdp {
 
             #[inline]
             fn reset(&mut self) {
-                self.input.set_pos(0);
+                let start = self.input.start_pos();
+
+                self.input.set_pos(start);
                 self.queue.clear();
                 self.queue_index.set(0);
                 self.failures.clear();
+                self.labels.clear();
                 self.fail_pos = 0;
+                self.stack.clear();
+                self.stack_ops.clear();
+                self.eoi_matched = false;
+                self.calls.clear();
+                self.holds.clear();
+                self.memo.clear();
+                self.cut = None;
+                self.cut_failed = false;
+                self.errors.clear();
             }
 
             #[inline]
@@ -257,11 +781,11 @@ macro_rules! impl_rdp {
                 &mut self.queue
             }
 
-            fn queue_with_captures(&self) -> Vec<(Token<Rule>, String)> {
+            fn queue_with_captures(&self) -> Vec<(Token<Rule>, T::Slice)> {
                 self.queue
                     .clone()
                     .into_iter()
-                    .map(|t| (t, self.input().slice(t.start, t.end).to_owned()))
+                    .map(|t| (t, self.input().slice(t.start, t.end)))
                     .collect()
             }
 
@@ -301,6 +825,11 @@ macro_rules! impl_rdp {
                 }
             }
 
+            #[inline]
+            fn cut_failed(&self) -> bool {
+                self.cut_failed
+            }
+
             #[inline]
             fn is_atomic(&self) -> bool {
                 self.atomic
@@ -311,30 +840,29 @@ macro_rules! impl_rdp {
                 self.atomic = value;
             }
 
+            #[inline]
+            fn is_packrat(&self) -> bool {
+                self.packrat
+            }
+
+            #[inline]
+            fn set_packrat(&mut self, value: bool) {
+                self.packrat = value;
+            }
+
             #[inline]
             fn track(&mut self, failed: Rule, pos: usize) {
                 if self.atomic {
                     return
                 }
 
-                if self.failures.is_empty() {
+                if self.__track_pos(pos) {
                     self.failures.push(failed);
-
-                    self.fail_pos = pos;
-                } else {
-                    if pos == self.fail_pos {
-                        self.failures.push(failed);
-                    } else if pos > self.fail_pos {
-                        self.failures.clear();
-                        self.failures.push(failed);
-
-                        self.fail_pos = pos;
-                    }
                 }
             }
 
             fn tracked_len_pos(&self) -> (usize, usize) {
-                (self.failures.len(), self.fail_pos)
+                (self.failures.len() + self.labels.len(), self.fail_pos)
             }
 
             fn expected(&mut self) -> (Vec<Rule>, usize) {
@@ -344,14 +872,81 @@ macro_rules! impl_rdp {
                 (self.failures.iter().cloned().collect(), self.fail_pos)
             }
 
+            fn expected_labels(&self) -> (Vec<&'static str>, usize) {
+                (Self::__unique_labels(&self.labels), self.fail_pos)
+            }
+
+            fn errors(&self) -> Vec<(Vec<Rule>, Vec<&'static str>, usize)> {
+                self.queue
+                    .iter()
+                    .filter(|token| token.rule == Rule::error)
+                    .filter_map(|token| {
+                        self.errors
+                            .iter()
+                            .rev()
+                            .find(|&&(start, _)| start == token.start)
+                            .map(|&(_, ref error)| error.clone())
+                    })
+                    .collect()
+            }
+
             #[inline]
-            fn stack(&self) -> &Vec<String> {
+            fn stack(&self) -> &Vec<(usize, usize)> {
+                self.stack_uses.set(self.stack_uses.get() + 1);
+
                 &self.stack
             }
 
             #[inline]
-            fn stack_mut(&mut self) -> &mut Vec<String> {
-                &mut self.stack
+            fn stack_push(&mut self, capture: (usize, usize)) {
+                self.stack_uses.set(self.stack_uses.get() + 1);
+                self.stack.push(capture);
+                self.stack_ops.push(None);
+            }
+
+            #[inline]
+            fn stack_pop(&mut self) -> Option<(usize, usize)> {
+                self.stack_uses.set(self.stack_uses.get() + 1);
+
+                let capture = self.stack.pop();
+
+                if capture.is_some() {
+                    self.stack_ops.push(capture);
+                }
+
+                capture
+            }
+
+            #[inline]
+            fn snapshot(&self) -> $crate::Snapshot {
+                $crate::Snapshot {
+                    pos:         self.input.pos(),
+                    queue_len:   self.queue.len(),
+                    stack_ops:   self.stack_ops.len(),
+                    errors_len:  self.errors.len(),
+                    eoi_matched: self.eoi_matched,
+                    atomic:      self.atomic
+                }
+            }
+
+            #[inline]
+            fn restore(&mut self, snapshot: $crate::Snapshot) {
+                self.input.set_pos(snapshot.pos);
+                self.queue.truncate(snapshot.queue_len);
+                self.errors.truncate(snapshot.errors_len);
+
+                // undo pushes (None) and pops (Some) in reverse order
+                while self.stack_ops.len() > snapshot.stack_ops {
+                    match self.stack_ops.pop().unwrap() {
+                        Some(capture) => self.stack.push(capture),
+                        None => {
+                            self.stack.pop();
+                        }
+                    }
+                }
+
+                self.eoi_matched = snapshot.eoi_matched;
+                self.atomic = snapshot.atomic;
             }
         }
     };
@@ -369,6 +964,8 @@ mod tests {
             one = { ["a"]+ }
             comment = _{ ["//"] ~ (!["\n"] ~ any)* ~ ["\n"] }
             soi_eoi = { soi ~ ["a"] ~ eoi }
+            #[memo]
+            memo = { ["a"] ~ ["b"] }
             whitespace = _{ [" "] }
         }
     }
@@ -389,6 +986,66 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn restore() {
+        let input = StringInput::new("asdasdf");
+        let mut parser = Rdp::new(input);
+
+        parser.stack_push((0, 1));
+
+        let snapshot = parser.snapshot();
+
+        assert!(parser.input_mut().match_string("asd"));
+
+        parser.stack_push((1, 2));
+
+        assert_eq!(parser.stack_pop(), Some((1, 2)));
+        assert_eq!(parser.stack_pop(), Some((0, 1)));
+        assert_eq!(parser.stack_pop(), None);
+
+        parser.stack_push((2, 3));
+        parser.set_atomic(true);
+
+        parser.restore(snapshot);
+
+        assert_eq!(parser.input().pos(), 0);
+        assert_eq!(parser.stack(), &vec![(0, 1)]);
+        assert!(!parser.is_atomic());
+    }
+
+    #[test]
+    fn memoize() {
+        let mut parser = Rdp::new(StringInput::new("ab"));
+
+        assert!(parser.memo());
+        assert_eq!(parser.memo.len(), 1);
+
+        parser.input_mut().set_pos(0);
+        parser.queue_mut().clear();
+
+        assert!(parser.memo());
+        assert_eq!(parser.queue(), &vec![Token::new(Rule::memo, 0, 2)]);
+
+        parser.memo.insert((Rule::memo, 0, false), (None, (vec![], vec![], 0)));
+        parser.input_mut().set_pos(0);
+
+        assert!(!parser.memo());
+    }
+
+    #[test]
+    fn packrat() {
+        let mut parser = Rdp::new(StringInput::new("(())"));
+
+        assert!(parser.expression());
+        assert!(parser.memo.is_empty());
+
+        parser.reset();
+        parser.set_packrat(true);
+
+        assert!(parser.expression());
+        assert!(!parser.memo.is_empty());
+    }
+
     #[test]
     fn end() {
         let input = StringInput::new("asdasdf");
@@ -502,13 +1159,13 @@ mod tests {
         assert!(!parser.end());
 
         let queue = vec![
-            (Token::new(Rule::paren, 0, 7), "(  ( ))".to_owned()),
-            (Token::new(Rule::paren, 3, 6), "( )".to_owned()),
-            (Token::new(Rule::paren, 7, 18), "(( () )() )".to_owned()),
-            (Token::new(Rule::paren, 8, 14), "( () )".to_owned()),
-            (Token::new(Rule::paren, 10, 12), "()".to_owned()),
-            (Token::new(Rule::paren, 14, 16), "()".to_owned()),
-            (Token::new(Rule::paren, 18, 20), "()".to_owned()),
+            (Token::new(Rule::paren, 0, 7), "(  ( ))"),
+            (Token::new(Rule::paren, 3, 6), "( )"),
+            (Token::new(Rule::paren, 7, 18), "(( () )() )"),
+            (Token::new(Rule::paren, 8, 14), "( () )"),
+            (Token::new(Rule::paren, 10, 12), "()"),
+            (Token::new(Rule::paren, 14, 16), "()"),
+            (Token::new(Rule::paren, 18, 20), "()"),
         ];
 
         assert_eq!(parser.queue_with_captures(), queue);
@@ -0,0 +1,52 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// A `struct` representing the state of a parser that gets restored on backtracking. It is
+/// returned by [`Parser::snapshot`](trait.Parser#tymethod.snapshot) and consumed by
+/// [`Parser::restore`](trait.Parser#tymethod.restore).
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate pest;
+/// # use pest::prelude::*;
+/// # fn main() {
+/// impl_rdp! {
+///     grammar! {
+///         a = { ["a"] }
+///     }
+/// }
+///
+/// let mut parser = Rdp::new(StringInput::new("aa"));
+///
+/// let snapshot = parser.snapshot();
+///
+/// assert!(parser.a());
+/// parser.stack_push((0, 1));
+///
+/// parser.restore(snapshot);
+///
+/// assert_eq!(parser.input().pos(), 0);
+/// assert!(parser.queue().is_empty());
+/// assert!(parser.stack().is_empty());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Snapshot {
+    /// position in `Input`
+    pub pos:         usize,
+    /// length of the queue
+    pub queue_len:   usize,
+    /// number of changes made to the stack
+    pub stack_ops:   usize,
+    /// number of errors recovered from
+    pub errors_len:  usize,
+    /// whether end-of-input was matched
+    pub eoi_matched: bool,
+    /// whether the parser was atomic
+    pub atomic:      bool
+}
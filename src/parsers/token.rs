@@ -41,9 +41,9 @@ impl<Rule> Token<Rule> {
     /// ```
     pub fn new(rule: Rule, start: usize, end: usize) -> Token<Rule> {
         Token {
-            rule:  rule,
-            start: start,
-            end:   end
+            rule,
+            start,
+            end
         }
     }
 }
@@ -5,6 +5,11 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-//! A `mod` that contains `pest::Input`, `pest::Parser`, `pest::StringInput`, and `pest::Token`.
+//! A `mod` that contains `pest::BytesInput`, `pest::ChunkedInput`, `pest::DecodedInput`,
+//! `pest::EmbeddedInput`, `pest::Input`, `pest::KindInput`, `pest::Lexeme`,
+//! `pest::NormalizedInput`, `pest::OwnedInput`, `pest::Parser`, `pest::ReaderInput`,
+//! `pest::SharedStr`, `pest::SliceInput`, `pest::StringInput`, and `pest::Token`.
 
-pub use super::{Input, Parser, StringInput, Token};
+pub use super::{BytesInput, ChunkedInput, DecodedInput, EmbeddedInput, Input, KindInput, Lexeme,
+                NormalizedInput, OwnedInput, Parser, ReaderInput, SharedStr, SliceInput,
+                StringInput, Token};
@@ -27,8 +27,8 @@
 /// | `mut item: fn()` | call matcher `fn` and store mutable result in `item` |
 ///
 /// > *Note:* Lifetime elision works by using the lifetime of the `Parser` instance. To use the
-/// lifetime of the `Input` instance in your process methods, use the explicit `'input` lifetime
-/// as shown in [this test](https://github.com/dragostis/pest/blob/master/tests/lifetimes.rs#L25).
+/// > lifetime of the `Input` instance in your process methods, use the explicit `'input` lifetime
+/// > as shown in [this test](https://github.com/dragostis/pest/blob/master/tests/lifetimes.rs#L25).
 ///
 /// Patterns are constructed using a comma-separated list of the items above in a matcher method:
 ///
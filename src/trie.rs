@@ -0,0 +1,165 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::cmp::Reverse;
+
+/// A `struct` that stores a set of literals as a byte trie in order to match the longest one of
+/// them in a single pass. `grammar!` compiles alternations of pure literals, like
+/// `["<"] | ["<="]`, into a `Trie` that is matched with
+/// [`Input::match_trie`](trait.Input#method.match_trie), so the order of the alternatives does not
+/// matter.
+///
+/// # Examples
+///
+/// ```
+/// # use pest::Trie;
+/// let trie = Trie::new(&["<", "<=", "=="]);
+///
+/// assert_eq!(trie.matches(b"<=>").collect::<Vec<_>>(), vec![1, 2]);
+/// assert_eq!(trie.matches(b"=<").next(), None);
+/// ```
+#[derive(Debug)]
+pub struct Trie {
+    nodes: Vec<Node>,
+    literals: Vec<Vec<u8>>
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    edges: Vec<(u8, usize)>,
+    terminal: bool
+}
+
+impl Trie {
+    /// Creates a new `Trie` from a set of `literals`. Duplicates are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pest::Trie;
+    /// let trie = Trie::new(&["if", "in", "if"]);
+    ///
+    /// assert_eq!(trie.literals().len(), 2);
+    /// ```
+    pub fn new<L: AsRef<[u8]>>(literals: &[L]) -> Trie {
+        let mut trie = Trie {
+            nodes: vec![Node::default()],
+            literals: vec![]
+        };
+
+        for literal in literals {
+            trie.insert(literal.as_ref());
+        }
+
+        trie.literals.sort_by_key(|literal| Reverse(literal.len()));
+
+        trie
+    }
+
+    /// Returns the literals of a `Trie`, longest first.
+    pub fn literals(&self) -> &[Vec<u8>] {
+        &self.literals
+    }
+
+    /// Returns the lengths of all literals of a `Trie` that `bytes` starts with, shortest first.
+    pub fn matches<'a>(&'a self, bytes: &'a [u8]) -> Matches<'a> {
+        Matches {
+            trie: self,
+            bytes,
+            node: Some(0),
+            len: 0
+        }
+    }
+
+    fn insert(&mut self, literal: &[u8]) {
+        let mut node = 0;
+
+        for &byte in literal {
+            node = match self.nodes[node].edges.binary_search_by_key(&byte, |&(b, _)| b) {
+                Ok(i) => self.nodes[node].edges[i].1,
+                Err(i) => {
+                    let next = self.nodes.len();
+
+                    self.nodes.push(Node::default());
+                    self.nodes[node].edges.insert(i, (byte, next));
+
+                    next
+                }
+            };
+        }
+
+        if !self.nodes[node].terminal {
+            self.nodes[node].terminal = true;
+            self.literals.push(literal.to_vec());
+        }
+    }
+
+    #[inline]
+    fn next(&self, node: usize, byte: u8) -> Option<usize> {
+        let edges = &self.nodes[node].edges;
+
+        edges.binary_search_by_key(&byte, |&(b, _)| b).ok().map(|i| edges[i].1)
+    }
+}
+
+/// An `Iterator` over the lengths of the literals of a [`Trie`](struct.Trie) that some bytes
+/// start with, created by [`Trie::matches`](struct.Trie#method.matches).
+pub struct Matches<'a> {
+    trie: &'a Trie,
+    bytes: &'a [u8],
+    node: Option<usize>,
+    len: usize
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while let Some(node) = self.node {
+            let len = self.len;
+
+            self.node = self.bytes.get(len).and_then(|&byte| self.trie.next(node, byte));
+            self.len += 1;
+
+            if self.trie.nodes[node].terminal {
+                return Some(len);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Trie;
+
+    #[test]
+    fn matches() {
+        let trie = Trie::new(&["=", "==", "===", "!="]);
+
+        assert_eq!(trie.matches(b"==!").collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(trie.matches(b"!=").collect::<Vec<_>>(), vec![2]);
+        assert_eq!(trie.matches(b"!").count(), 0);
+        assert_eq!(trie.matches(b"").count(), 0);
+    }
+
+    #[test]
+    fn empty_literal() {
+        let trie = Trie::new(&["", "a"]);
+
+        assert_eq!(trie.matches(b"b").collect::<Vec<_>>(), vec![0]);
+        assert_eq!(trie.matches(b"a").collect::<Vec<_>>(), vec![0, 1]);
+    }
+
+    #[test]
+    fn literals() {
+        let trie = Trie::new(&["a", "abc", "ab", "abc"]);
+
+        assert_eq!(trie.literals(), &[b"abc".to_vec(), b"ab".to_vec(), b"a".to_vec()]);
+    }
+}
@@ -0,0 +1,107 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A `mod` with Unicode general category and derived property predicates, used by the
+//! [Unicode classes](../macro.grammar!#unicode-classes) of `grammar!`.
+
+mod tables;
+
+#[inline]
+fn contains(table: &[(u32, u32)], c: char) -> bool {
+    let c = c as u32;
+
+    table.binary_search_by(|&(left, right)| {
+        if right < c {
+            ::std::cmp::Ordering::Less
+        } else if left > c {
+            ::std::cmp::Ordering::Greater
+        } else {
+            ::std::cmp::Ordering::Equal
+        }
+    }).is_ok()
+}
+
+macro_rules! predicates {
+    ( $( $(#[$attr:meta])* $name:ident $table:ident ),* ) => {
+        $(
+            $(#[$attr])*
+            #[inline]
+            pub fn $name(c: char) -> bool {
+                contains(tables::$table, c)
+            }
+        )*
+    };
+}
+
+predicates! {
+    /// Returns whether `c` has the `Alphabetic` property.
+    is_alphabetic ALPHABETIC,
+    /// Returns whether `c` is in the `Decimal_Number` (`Nd`) general category.
+    is_decimal_number DECIMAL_NUMBER,
+    /// Returns whether `c` is in the `Letter` (`L`) general category.
+    is_letter LETTER,
+    /// Returns whether `c` is in the `Lowercase_Letter` (`Ll`) general category.
+    is_lowercase_letter LOWERCASE_LETTER,
+    /// Returns whether `c` is in the `Mark` (`M`) general category.
+    is_mark MARK,
+    /// Returns whether `c` is in the `Number` (`N`) general category.
+    is_number NUMBER,
+    /// Returns whether `c` is in the `Punctuation` (`P`) general category.
+    is_punctuation PUNCTUATION,
+    /// Returns whether `c` is in the `Symbol` (`S`) general category.
+    is_symbol SYMBOL,
+    /// Returns whether `c` is in the `Uppercase_Letter` (`Lu`) general category.
+    is_uppercase_letter UPPERCASE_LETTER,
+    /// Returns whether `c` has the `White_Space` property.
+    is_white_space WHITE_SPACE,
+    /// Returns whether `c` has the `XID_Continue` property, i.e. it can continue an identifier.
+    is_xid_continue XID_CONTINUE,
+    /// Returns whether `c` has the `XID_Start` property, i.e. it can start an identifier.
+    is_xid_start XID_START
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers() {
+        assert!(is_xid_start('a'));
+        assert!(is_xid_start('λ'));
+        assert!(is_xid_start('嗨'));
+        assert!(!is_xid_start('_'));
+        assert!(!is_xid_start('1'));
+        assert!(is_xid_continue('_'));
+        assert!(is_xid_continue('١'));
+        assert!(!is_xid_continue('-'));
+    }
+
+    #[test]
+    fn categories() {
+        assert!(is_letter('ж'));
+        assert!(!is_letter('1'));
+        assert!(is_uppercase_letter('Ж'));
+        assert!(is_lowercase_letter('ж'));
+        assert!(is_decimal_number('٣'));
+        assert!(!is_decimal_number('Ⅻ'));
+        assert!(is_number('Ⅻ'));
+        assert!(is_mark('\u{301}'));
+        assert!(is_punctuation('«'));
+        assert!(is_symbol('€'));
+        assert!(is_white_space('\u{3000}'));
+        assert!(!is_white_space('\u{200b}'));
+        assert!(!is_alphabetic('\u{10ffff}'));
+    }
+
+    #[test]
+    fn agrees_with_std() {
+        for c in (0..0x100).filter_map(::std::char::from_u32) {
+            assert_eq!(is_alphabetic(c), c.is_alphabetic(), "{:?}", c);
+            assert_eq!(is_white_space(c), c.is_whitespace(), "{:?}", c);
+        }
+    }
+}
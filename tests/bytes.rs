@@ -0,0 +1,78 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    slice: &'input [u8];
+
+    grammar! {
+        message = { magic ~ field* ~ eoi }
+        magic   = { [b"\xca\xfe"] }
+        field   = { tag ~ [push(value)] ~ [b"\x00"] ~ [pop()] }
+        tag     = { [b'\x01'..b'\x7f'] }
+        value   = { [b'\x80'..b'\xff']+ }
+        latin   = { [i"caf"] ~ [b"\xe9"] }
+    }
+
+    process! {
+        values(&self) -> Vec<&'input [u8]> {
+            (_: field, _: tag, &value: value, mut tail: values()) => {
+                tail.insert(0, value);
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+#[test]
+fn message() {
+    let mut parser = Rdp::new(BytesInput::new(b"\xca\xfe\x01\xff\x00\xff\x02\x80\x81\x00\x80\x81"));
+
+    assert!(parser.message());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::message, 0, 12),
+        Token::new(Rule::magic, 0, 2),
+        Token::new(Rule::field, 2, 6),
+        Token::new(Rule::tag, 2, 3),
+        Token::new(Rule::value, 3, 4),
+        Token::new(Rule::field, 6, 12),
+        Token::new(Rule::tag, 6, 7),
+        Token::new(Rule::value, 7, 9)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+
+    parser.set_queue_index(2);
+
+    assert_eq!(parser.values(), vec![&b"\xff"[..], &b"\x80\x81"[..]]);
+}
+
+#[test]
+fn mismatched_pop() {
+    let mut parser = Rdp::new(BytesInput::new(b"\xca\xfe\x01\xff\x00\xfe"));
+
+    assert!(!parser.message());
+    assert_eq!(parser.expected(), (vec![Rule::eoi, Rule::field], 2));
+}
+
+#[test]
+fn latin1() {
+    let mut parser = Rdp::new(BytesInput::new(b"CAF\xe9"));
+
+    assert!(parser.latin());
+    assert!(parser.end());
+    assert_eq!(parser.queue_with_captures(),
+               vec![(Token::new(Rule::latin, 0, 4), &b"CAF\xe9"[..])]);
+}
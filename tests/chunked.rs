@@ -0,0 +1,72 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use std::borrow::Cow;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    slice: Cow<'input, str>;
+
+    grammar! {
+        call      = { ident ~ ["("] ~ (ident ~ ([","] ~ ident)*)? ~ [")"] ~ eoi }
+        ident     = @{ [unicode(XID_START)] ~ [unicode(XID_CONTINUE)]* }
+        keyword   = { [i"select"] }
+
+        whitespace = _{ [" "] }
+    }
+
+    process! {
+        idents(&self) -> Vec<Cow<'input, str>> {
+            (&ident: ident, mut tail: idents()) => {
+                tail.insert(0, ident);
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+#[test]
+fn split_call() {
+    let chunks = vec!["pri", "nt(für", "st", ", ", "b)"];
+    let mut parser = Rdp::new(ChunkedInput::new(chunks));
+
+    assert!(parser.call());
+    assert!(parser.end());
+
+    parser.set_queue_index(1);
+
+    let idents = parser.idents();
+
+    assert_eq!(idents, vec!["print", "fürst", "b"]);
+    assert!(match idents[2] {
+        Cow::Borrowed(_) => true,
+        Cow::Owned(_) => false
+    });
+}
+
+#[test]
+fn insensitive() {
+    let mut parser = Rdp::new(ChunkedInput::new(vec!["SEL", "ect"]));
+
+    assert!(parser.keyword());
+    assert!(parser.end());
+}
+
+#[test]
+fn expected() {
+    let mut parser = Rdp::new(ChunkedInput::new(vec!["f(a", ", )"]));
+
+    assert!(!parser.call());
+    assert_eq!(parser.expected(), (vec![Rule::ident], 5));
+    assert_eq!(parser.input().line_col(5), (1, 6));
+}
@@ -0,0 +1,74 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+use pest::Encoding;
+
+impl_rdp! {
+    slice: SharedStr;
+
+    grammar! {
+        csv    = { record* ~ eoi }
+        record = { field ~ ([";"] ~ field)* ~ ["\r\n"] }
+        field  = @{ [unicode(LETTER)]+ }
+    }
+
+    process! {
+        fields(&self) -> Vec<SharedStr> {
+            (&field: field, mut tail: fields()) => {
+                tail.insert(0, field);
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+fn utf16le(text: &str) -> Vec<u8> {
+    let mut bytes = vec![0xff, 0xfe];
+
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+
+    bytes
+}
+
+#[test]
+fn utf16() {
+    let bytes = utf16le("Zoë;Łódź\r\n");
+    let mut parser = Rdp::new(DecodedInput::new(&bytes));
+
+    assert!(parser.csv());
+    assert_eq!(parser.input().encoding(), Encoding::Utf16Le);
+
+    parser.set_queue_index(2);
+
+    assert_eq!(parser.fields(), vec!["Zoë", "Łódź"]);
+
+    let lodz = parser.queue()[3];
+
+    assert_eq!(parser.input().original_pos(lodz.start), 10);
+    assert_eq!(parser.input().original_pos(lodz.end), 18);
+}
+
+#[test]
+fn windows_1252() {
+    let mut parser = Rdp::new(DecodedInput::new(b"Ren\xe9e;Fran\xe7ois\r\nNo\xebl;3\r\n"));
+
+    assert!(!parser.csv());
+
+    let (expected, pos) = parser.expected();
+
+    assert_eq!(expected, vec![Rule::field]);
+    assert_eq!(parser.input().original_pos(pos), 21);
+    assert_eq!(parser.input().line_col(pos), (2, 6));
+}
@@ -0,0 +1,76 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    grammar! {
+        query  = { soi ~ ["SELECT"] ~ column ~ ([","] ~ column)* ~ eoi }
+        column = @{ ['a'..'z']+ }
+
+        whitespace = _{ [" "] | ["\n"] }
+    }
+
+    process! {
+        columns(&self) -> Vec<&'input str> {
+            (&column: column, mut tail: columns()) => {
+                tail.insert(0, column);
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+const FILE: &str = "fn main() {\n    let q = \"SELECT id,\n        name\";\n}\n";
+
+fn fragment() -> EmbeddedInput<StringInput<'static>, &'static str> {
+    let start = FILE.find("SELECT").unwrap();
+    let end = FILE.rfind('"').unwrap();
+
+    EmbeddedInput::new(StringInput::new(&FILE[start..end]), "main.rs", start, 2, 14)
+}
+
+#[test]
+fn tokens() {
+    let mut parser = Rdp::new(fragment());
+
+    assert!(parser.query());
+    assert!(parser.end());
+
+    assert_eq!(parser.queue()[0], Token::new(Rule::query, 25, 48));
+    assert_eq!(parser.queue()[1], Token::new(Rule::column, 32, 34));
+    assert_eq!(&FILE[44..48], "name");
+
+    parser.set_queue_index(1);
+
+    assert_eq!(parser.columns(), vec!["id", "name"]);
+}
+
+#[test]
+fn errors() {
+    let mut parser = Rdp::new(fragment());
+
+    parser.input_mut().set_pos(36);
+
+    assert!(!parser.query());
+
+    let (_, pos) = parser.expected();
+
+    assert_eq!(pos, 36);
+    assert_eq!(parser.input().line_col(pos), (3, 1));
+    assert_eq!(parser.input().line_col(32), (2, 21));
+    assert_eq!(parser.input().line_col(44), (3, 9));
+
+    parser.reset();
+
+    assert!(parser.query());
+}
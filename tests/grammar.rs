@@ -17,14 +17,76 @@ impl_rdp! {
         rep_zero = { ["a"]* ~ eoi }
         rep_one = { ["a"]+ }
         opt = { ["a"]? }
+        rep_exact = { ["a"]{3} }
+        rep_min = { ["a"]{2,} ~ eoi }
+        rep_max = { ["a"]{,2} ~ ["b"] }
+        rep_min_max = { digit{1, 3} ~ eoi }
+        rep_atomic = @{ digit{2} }
         pres = { &["a"] }
         abs = { !(["a"] | ["b"]) ~ any }
         ins = { [i"seLeCT"] }
         push_pop = { [push(opt)] ~ [pop()] }
         pop_twice = { [push(opt)] ~ [pop()] ~ [pop()] }
         push_peek_pop = { [push(opt)] ~ [peek()] ~ [pop()] }
+        peek_empty = { [peek()] }
+        push_drop = { [push(opt)] ~ [push(letter)] ~ [drop()] ~ [pop()] }
+        drop_empty = { [drop()] ~ ["a"] }
+        push_pop_all = { [push(opt)] ~ [push(letter)] ~ [pop_all()] }
+        push_peek_all = { [push(opt)] ~ [push(letter)] ~ [peek_all()] }
+        letter = { ['a'..'z'] }
+        brackets = @{
+            [push(open)] ~ (brackets | ["x"]) ~ [pop("(" => ")", "[" => "]", "{" => "}")]
+        }
+        peek_map = { [push(open)] ~ [peek("(" => ")")] ~ [pop("(" => ")")] }
+        open = { ["("] | ["["] | ["{"] }
+        push_fail = { [push(opt)] ~ ["x"] | ["a"] }
+        push_rep = { ([push(digit)] ~ [";"])* }
+        pop_fail = { [push(opt)] ~ ([pop()] ~ ["x"] | ["a-"]) ~ [pop()] }
+        eoi_fail = { ["a"] ~ eoi ~ ["b"] | ["a"] }
         digit = { ['0'..'9'] }
+        ident = @{ ['a'..'z', 'A'..'Z', '_'] ~ ['a'..'z', 'A'..'Z', '_', '0'..'9']* }
+        unquoted = { [^ '"', '\\']+ }
+        not_digit = { [^ '0'..'9'] }
+        comparison = { ["<"] | [">"] | ["<="] | [">="] | ["=="] | ["≤"] }
+        longest = { (["in"] | ["int"] | ["i"]) ~ eoi }
+        statement = { [k"if"] ~ name | name }
+        name = k@{ ['a'..'z'] ~ ident_char* }
+        keyword = _{ [k"if"] | [k"in"] | [k"else"] }
+        ident_char = _{ ['a'..'z', 'A'..'Z', '_', '0'..'9'] }
         number = { ['0'..'9']+ }
+        #[left_recursive]
+        sum = { sum ~ ["+"] ~ number | number }
+        #[left_recursive]
+        member = { access | number }
+        #[left_recursive]
+        access = { member ~ ["."] ~ number }
+        #[memo]
+        #[left_recursive]
+        memo_sum = { memo_sum ~ ["+"] ~ number | number }
+        #[memo]
+        memo_word = @{ ['a'..'z']+ }
+        #[memo]
+        memo_silent = _{ memo_word ~ ["!"] | memo_word ~ ["?"] }
+        opener = { [push(memo_word)] }
+        two = { opener ~ ["!"] | opener ~ ["|"] ~ [pop()] }
+        definition = { ["fn"] ~ [cut()] ~ ident ~ ["("] ~ [")"] | ["fn"] ~ ["*"] }
+        declaration = { definition ~ [";"] | ident ~ any }
+        cut_opt = { (["a"] ~ [cut()] ~ digit)? ~ ["b"] }
+        cut_literal = { ["c"] ~ (["a"] ~ [cut()] ~ ["x"])? ~ ["b"] }
+        call = { ident ~ ["."] ~ ident ~ ["("] ~ digit | ident ~ [cut()] ~ ["."] ~ digit }
+        block = { (![k"end"] ~ [recover(assignment, sync)] ~ [";"]?)* ~ [k"end"] }
+        assignment = { ident ~ ["="] ~ number }
+        definitions = { ([recover(definition, sync)] ~ [";"]?)* ~ eoi }
+        sync = _{ [";"] | [k"end"] }
+        repeated = { [recover(assignment, sync)]* ~ [";"] ~ assignment }
+        nothing = { [""] }
+        retried = { [recover(assignment, sync)] ~ (nothing ~ [recover(number, sync)] ~ ["!"])? ~
+                    [";"] }
+        #[label("a list")]
+        list = { ["["] ~ digit ~ ([","] ~ digit)* ~ #[label("a closing `]`")] ["]"] }
+        #[label("a value")]
+        value = _{ list | number }
+        labeled_block = { (![k"end"] ~ [recover(value, sync)] ~ [";"]?)* ~ [k"end"] }
         plus = { ["+"] }
         times = { ["*"] }
         power = { ["^"] }
@@ -162,6 +224,84 @@ fn rep_one_wrong() {
     assert_eq!(parser.expected(), (vec![Rule::rep_one], 0));
 }
 
+#[test]
+fn rep_exact() {
+    let mut parser = Rdp::new(StringInput::new("a a aa"));
+
+    assert!(parser.rep_exact());
+    assert_eq!(parser.input().pos(), 5);
+
+    let mut parser = Rdp::new(StringInput::new("a a"));
+
+    assert!(!parser.rep_exact());
+    assert_eq!(parser.input().pos(), 0);
+    assert_eq!(parser.expected(), (vec![Rule::rep_exact], 0));
+}
+
+#[test]
+fn rep_min() {
+    let mut parser = Rdp::new(StringInput::new("a a a a"));
+
+    assert!(parser.rep_min());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("a"));
+
+    assert!(!parser.rep_min());
+}
+
+#[test]
+fn rep_max() {
+    let mut parser = Rdp::new(StringInput::new("b"));
+
+    assert!(parser.rep_max());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("a a b"));
+
+    assert!(parser.rep_max());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("a a a b"));
+
+    assert!(!parser.rep_max());
+}
+
+#[test]
+fn rep_min_max() {
+    let mut parser = Rdp::new(StringInput::new("1 2 3"));
+
+    assert!(parser.rep_min_max());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::rep_min_max, 0, 5),
+        Token::new(Rule::digit, 0, 1),
+        Token::new(Rule::digit, 2, 3),
+        Token::new(Rule::digit, 4, 5)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+
+    let mut parser = Rdp::new(StringInput::new("1 2 3 4"));
+
+    assert!(!parser.rep_min_max());
+    assert_eq!(parser.queue(), &vec![]);
+    assert_eq!(parser.expected(), (vec![Rule::eoi], 6));
+}
+
+#[test]
+fn rep_atomic() {
+    let mut parser = Rdp::new(StringInput::new("12"));
+
+    assert!(parser.rep_atomic());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("1 2"));
+
+    assert!(!parser.rep_atomic());
+}
+
 #[test]
 fn opt_empty() {
     let mut parser = Rdp::new(StringInput::new(""));
@@ -356,6 +496,439 @@ fn comment_between_subrules() {
     assert!(parser.end());
 }
 
+#[test]
+fn class() {
+    let mut parser = Rdp::new(StringInput::new("_Abc_09"));
+
+    assert!(parser.ident());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("0abc"));
+
+    assert!(!parser.ident());
+    assert_eq!(parser.expected(), (vec![Rule::ident], 0));
+}
+
+#[test]
+fn negated_class() {
+    let mut parser = Rdp::new(StringInput::new("a 嗨\\\""));
+
+    assert!(parser.unquoted());
+    assert_eq!(parser.queue(), &vec![Token::new(Rule::unquoted, 0, 5)]);
+
+    let mut parser = Rdp::new(StringInput::new("x"));
+
+    assert!(parser.not_digit());
+
+    let mut parser = Rdp::new(StringInput::new("5"));
+
+    assert!(!parser.not_digit());
+
+    let mut parser = Rdp::new(StringInput::new(""));
+
+    assert!(!parser.not_digit());
+}
+
+#[test]
+fn longest_literal() {
+    let mut parser = Rdp::new(StringInput::new("<=>"));
+
+    assert!(parser.comparison());
+    assert_eq!(parser.queue(), &vec![Token::new(Rule::comparison, 0, 2)]);
+
+    let mut parser = Rdp::new(StringInput::new("≤"));
+
+    assert!(parser.comparison());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("="));
+
+    assert!(!parser.comparison());
+    assert_eq!(parser.expected(), (vec![Rule::comparison], 0));
+}
+
+#[test]
+fn longest_literal_in_parens() {
+    let mut parser = Rdp::new(StringInput::new("int"));
+
+    assert!(parser.longest());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("i"));
+
+    assert!(parser.longest());
+    assert!(parser.end());
+}
+
+#[test]
+fn keyword() {
+    let mut parser = Rdp::new(StringInput::new("if x"));
+
+    assert!(parser.statement());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::statement, 0, 4),
+        Token::new(Rule::name, 3, 4)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+
+    let mut parser = Rdp::new(StringInput::new("if"));
+
+    assert!(!parser.statement());
+    assert_eq!(parser.expected(), (vec![Rule::name], 2));
+}
+
+#[test]
+fn keyword_prefix() {
+    let mut parser = Rdp::new(StringInput::new("iffy"));
+
+    assert!(parser.statement());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::statement, 0, 4),
+        Token::new(Rule::name, 0, 4)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+}
+
+#[test]
+fn reserved_word() {
+    let mut parser = Rdp::new(StringInput::new("else"));
+
+    assert!(!parser.name());
+    assert_eq!(parser.expected(), (vec![Rule::name], 0));
+    assert_eq!(parser.input().pos(), 0);
+}
+
+#[test]
+fn left_recursion() {
+    let mut parser = Rdp::new(StringInput::new("1 + 2 + 3"));
+
+    assert!(parser.sum());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::sum, 0, 9),
+        Token::new(Rule::sum, 0, 5),
+        Token::new(Rule::sum, 0, 1),
+        Token::new(Rule::number, 0, 1),
+        Token::new(Rule::number, 4, 5),
+        Token::new(Rule::number, 8, 9)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+
+    let mut parser = Rdp::new(StringInput::new("1 +"));
+
+    assert!(parser.sum());
+    assert_eq!(parser.input().pos(), 1);
+}
+
+#[test]
+fn indirect_left_recursion() {
+    let mut parser = Rdp::new(StringInput::new("1.2.3"));
+
+    assert!(parser.member());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::member, 0, 5),
+        Token::new(Rule::access, 0, 5),
+        Token::new(Rule::member, 0, 3),
+        Token::new(Rule::access, 0, 3),
+        Token::new(Rule::member, 0, 1),
+        Token::new(Rule::number, 0, 1),
+        Token::new(Rule::number, 2, 3),
+        Token::new(Rule::number, 4, 5)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+
+    let mut parser = Rdp::new(StringInput::new("x"));
+
+    assert!(!parser.member());
+    assert_eq!(parser.expected(), (vec![Rule::access, Rule::number], 0));
+
+    let mut parser = Rdp::new(StringInput::new("1.2.3"));
+
+    assert!(parser.access());
+    assert!(parser.end());
+    assert_eq!(parser.queue()[0], Token::new(Rule::access, 0, 5));
+}
+
+#[test]
+fn memo() {
+    let mut parser = Rdp::new(StringInput::new("1 + 2 + 3"));
+
+    assert!(parser.memo_sum());
+    assert!(parser.end());
+    assert_eq!(parser.queue()[1], Token::new(Rule::memo_sum, 0, 5));
+
+    let mut parser = Rdp::new(StringInput::new("abc ?"));
+
+    assert!(parser.memo_silent());
+    assert!(parser.end());
+    assert_eq!(parser.queue(), &vec![Token::new(Rule::memo_word, 0, 3)]);
+}
+
+#[test]
+fn packrat() {
+    let mut parser = Rdp::new(StringInput::new("1 + 2 + 3"));
+
+    parser.set_packrat(true);
+
+    assert!(parser.sum());
+    assert!(parser.end());
+    assert_eq!(parser.queue().len(), 6);
+
+    parser.reset();
+
+    assert!(parser.sum());
+    assert!(parser.end());
+    assert_eq!(parser.queue().len(), 6);
+}
+
+#[test]
+fn cut() {
+    let mut parser = Rdp::new(StringInput::new("fn f()"));
+
+    assert!(parser.definition());
+    assert!(parser.end());
+    assert!(!parser.cut_failed());
+
+    let mut parser = Rdp::new(StringInput::new("fn *"));
+
+    assert!(!parser.definition());
+    assert!(parser.cut_failed());
+    assert_eq!(parser.expected(), (vec![Rule::ident], 3));
+
+    parser.reset();
+
+    assert!(!parser.cut_failed());
+}
+
+#[test]
+fn cut_scope() {
+    let mut parser = Rdp::new(StringInput::new("fn f() ?"));
+
+    assert!(parser.declaration());
+    assert!(!parser.cut_failed());
+    assert_eq!(parser.queue(), &vec![
+        Token::new(Rule::declaration, 0, 4),
+        Token::new(Rule::ident, 0, 2)
+    ]);
+}
+
+#[test]
+fn cut_optional() {
+    let mut parser = Rdp::new(StringInput::new("a1b"));
+
+    assert!(parser.cut_opt());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("b"));
+
+    assert!(parser.cut_opt());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("ab"));
+
+    assert!(!parser.cut_opt());
+    assert!(parser.cut_failed());
+    assert_eq!(parser.expected(), (vec![Rule::digit], 1));
+
+    let mut parser = Rdp::new(StringInput::new("cab"));
+
+    assert!(!parser.cut_literal());
+    assert!(parser.cut_failed());
+    assert_eq!(parser.expected(), (vec![Rule::cut_literal], 2));
+}
+
+#[test]
+fn cut_expected() {
+    let mut parser = Rdp::new(StringInput::new("a.b(x"));
+
+    assert!(!parser.call());
+    assert_eq!(parser.expected(), (vec![Rule::digit], 2));
+
+    let mut parser = Rdp::new(StringInput::new("a.b(1"));
+
+    assert!(parser.call());
+    assert!(parser.end());
+}
+
+#[test]
+fn recover() {
+    let mut parser = Rdp::new(StringInput::new("a = 1; b = ; c = 2; d end"));
+
+    assert!(parser.block());
+    assert!(parser.end());
+
+    let errors: Vec<_> = parser.queue().iter().filter(|t| t.rule == Rule::error).collect();
+
+    assert_eq!(errors, vec![&Token::new(Rule::error, 7, 11), &Token::new(Rule::error, 20, 22)]);
+    assert_eq!(parser.errors(), vec![
+        (vec![Rule::number], vec![], 11),
+        (vec![Rule::assignment], vec![], 20)
+    ]);
+}
+
+#[test]
+fn recover_nothing() {
+    let mut parser = Rdp::new(StringInput::new("a = 1 end"));
+
+    assert!(parser.block());
+    assert!(parser.end());
+    assert!(parser.errors().is_empty());
+
+    let mut parser = Rdp::new(StringInput::new("a = 1"));
+
+    assert!(!parser.block());
+    assert!(parser.errors().is_empty());
+}
+
+#[test]
+fn recover_empty() {
+    let mut parser = Rdp::new(StringInput::new("; a = 1;; end"));
+
+    assert!(parser.block());
+    assert!(parser.end());
+
+    let errors: Vec<_> = parser.queue().iter().filter(|t| t.rule == Rule::error).collect();
+
+    assert_eq!(errors, vec![&Token::new(Rule::error, 0, 0), &Token::new(Rule::error, 8, 8)]);
+    assert_eq!(parser.errors(), vec![
+        (vec![Rule::ident], vec![], 0),
+        (vec![Rule::ident], vec![], 8)
+    ]);
+}
+
+#[test]
+fn recover_empty_once() {
+    let mut parser = Rdp::new(StringInput::new("; a = 1"));
+
+    assert!(parser.repeated());
+    assert!(parser.end());
+    assert_eq!(parser.queue()[1], Token::new(Rule::error, 0, 0));
+    assert_eq!(parser.errors(), vec![(vec![Rule::ident], vec![], 0)]);
+}
+
+#[test]
+fn recover_backtrack() {
+    let mut parser = Rdp::new(StringInput::new(";"));
+
+    assert!(parser.retried());
+    assert!(parser.end());
+    assert_eq!(parser.errors(), vec![(vec![Rule::ident], vec![], 0)]);
+}
+
+#[test]
+fn recover_restore() {
+    let mut parser = Rdp::new(StringInput::new("a = ; end"));
+    let snapshot = parser.snapshot();
+
+    assert!(parser.block());
+    assert_eq!(parser.snapshot().errors_len, 1);
+
+    parser.restore(snapshot);
+
+    assert_eq!(parser.snapshot(), snapshot);
+}
+
+#[test]
+fn recover_cut() {
+    let mut parser = Rdp::new(StringInput::new("fn *; fn f();"));
+
+    assert!(parser.definitions());
+    assert!(parser.end());
+    assert!(!parser.cut_failed());
+
+    assert_eq!(parser.queue()[1], Token::new(Rule::error, 0, 4));
+    assert_eq!(parser.errors(), vec![(vec![Rule::ident], vec![], 3)]);
+}
+
+#[test]
+fn label_rule() {
+    let mut parser = Rdp::new(StringInput::new("x"));
+
+    assert!(!parser.list());
+    assert_eq!(parser.expected(), (vec![], 0));
+    assert_eq!(parser.expected_labels(), (vec!["a list"], 0));
+}
+
+#[test]
+fn label_silent_rule() {
+    let mut parser = Rdp::new(StringInput::new("x"));
+
+    assert!(!parser.value());
+    assert_eq!(parser.expected(), (vec![], 0));
+    assert_eq!(parser.expected_labels(), (vec!["a value"], 0));
+}
+
+#[test]
+fn label_expression() {
+    let mut parser = Rdp::new(StringInput::new("[1, 2"));
+
+    assert!(!parser.list());
+    assert_eq!(parser.expected(), (vec![], 5));
+    assert_eq!(parser.expected_labels(), (vec!["a closing `]`"], 5));
+}
+
+#[test]
+fn label_deeper_failure() {
+    let mut parser = Rdp::new(StringInput::new("[1, x]"));
+
+    assert!(!parser.list());
+    assert_eq!(parser.expected(), (vec![Rule::digit], 4));
+    assert!(parser.expected_labels().0.is_empty());
+}
+
+#[test]
+fn label_recover() {
+    let mut parser = Rdp::new(StringInput::new("1; x; [2 end"));
+
+    assert!(parser.labeled_block());
+    assert!(parser.end());
+    assert_eq!(parser.errors(), vec![
+        (vec![], vec!["a value"], 3),
+        (vec![], vec!["a closing `]`"], 9)
+    ]);
+}
+
+#[test]
+fn packrat_stack() {
+    let mut parser = Rdp::new(StringInput::new("abc|abc"));
+
+    parser.set_packrat(true);
+
+    assert!(parser.two());
+    assert!(parser.end());
+    assert!(parser.stack().is_empty());
+}
+
+#[test]
+fn packrat_expected() {
+    for input in &["abc|abd", "abc?", "1", "abc | 1"] {
+        let mut parser = Rdp::new(StringInput::new(input));
+        let mut packrat = Rdp::new(StringInput::new(input));
+
+        packrat.set_packrat(true);
+
+        assert_eq!(parser.two(), packrat.two());
+        assert_eq!(parser.expected(), packrat.expected());
+
+        parser.reset();
+        packrat.reset();
+
+        assert_eq!(parser.sum(), packrat.sum());
+        assert_eq!(parser.expected(), packrat.expected());
+    }
+}
+
 #[test]
 fn insensitive() {
     let mut parser = Rdp::new(StringInput::new("SeleCt"));
@@ -373,11 +946,87 @@ fn stack_push_pop() {
 }
 
 #[test]
-#[should_panic]
 fn stack_pop_twice() {
     let mut parser = Rdp::new(StringInput::new("a a a"));
 
-    assert!(parser.pop_twice());
+    assert!(!parser.pop_twice());
+    assert_eq!(parser.expected(), (vec![Rule::pop], 4));
+    assert!(parser.stack().is_empty());
+}
+
+#[test]
+fn stack_peek_empty() {
+    let mut parser = Rdp::new(StringInput::new("a"));
+
+    assert!(!parser.peek_empty());
+    assert_eq!(parser.expected(), (vec![Rule::peek], 0));
+}
+
+#[test]
+fn stack_drop() {
+    let mut parser = Rdp::new(StringInput::new("a b a"));
+
+    assert!(parser.push_drop());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("a"));
+
+    assert!(!parser.drop_empty());
+    assert_eq!(parser.expected(), (vec![Rule::drop], 0));
+}
+
+#[test]
+fn stack_pop_all() {
+    let mut parser = Rdp::new(StringInput::new("a b ba"));
+
+    assert!(parser.push_pop_all());
+    assert!(parser.end());
+    assert!(parser.stack().is_empty());
+
+    let mut parser = Rdp::new(StringInput::new("a b ab"));
+
+    assert!(!parser.push_pop_all());
+}
+
+#[test]
+fn stack_peek_all() {
+    let mut parser = Rdp::new(StringInput::new("a b ba"));
+
+    assert!(parser.push_peek_all());
+    assert!(parser.end());
+    assert_eq!(parser.stack().len(), 2);
+}
+
+#[test]
+fn stack_reset() {
+    let mut parser = Rdp::new(StringInput::new("a b ba"));
+
+    assert!(parser.push_peek_all());
+    assert_eq!(parser.stack().len(), 2);
+
+    parser.reset();
+
+    assert!(parser.stack().is_empty());
+    assert!(parser.push_peek_all());
+    assert!(parser.end());
+    assert_eq!(parser.stack(), &vec![(0, 1), (2, 3)]);
+}
+
+#[test]
+fn stack_mapping() {
+    let mut parser = Rdp::new(StringInput::new("([{x}])"));
+
+    assert!(parser.brackets());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("([x)]"));
+
+    assert!(!parser.brackets());
+
+    let mut parser = Rdp::new(StringInput::new("( ) )"));
+
+    assert!(parser.peek_map());
+    assert!(parser.end());
 }
 
 #[test]
@@ -387,3 +1036,39 @@ fn stack_push_peek_pop() {
     assert!(parser.push_peek_pop());
     assert!(parser.end());
 }
+
+#[test]
+fn stack_push_fail() {
+    let mut parser = Rdp::new(StringInput::new("a"));
+
+    assert!(parser.push_fail());
+    assert!(parser.end());
+    assert!(parser.stack().is_empty());
+}
+
+#[test]
+fn stack_push_rep() {
+    let mut parser = Rdp::new(StringInput::new("1; 2"));
+
+    assert!(parser.push_rep());
+    assert_eq!(parser.input().pos(), 2);
+    assert_eq!(parser.stack(), &vec![(0, 1)]);
+}
+
+#[test]
+fn stack_pop_fail() {
+    let mut parser = Rdp::new(StringInput::new("aa-a"));
+
+    assert!(parser.pop_fail());
+    assert!(parser.end());
+    assert!(parser.stack().is_empty());
+}
+
+#[test]
+fn eoi_fail() {
+    let mut parser = Rdp::new(StringInput::new("a"));
+
+    assert!(parser.eoi_fail());
+    assert!(parser.end());
+    assert!(!parser.eoi_matched());
+}
@@ -1269,3 +1269,23 @@ fn shebang() {
 
     assert_eq!(parser.queue(), &queue);
 }
+
+#[test]
+fn packrat() {
+    let program = "local t = { f(a)(b).c, [g:h(1)] = \"x\" .. y[2] }\n\
+                   for i = 1, 2 do print(t[i]:upper(), (f)(i) ^ 2) end";
+
+    let mut parser = Rdp::new(StringInput::new(program));
+
+    assert!(parser.chunk());
+
+    let queue = parser.queue().clone();
+
+    let mut parser = Rdp::new(StringInput::new(program));
+
+    parser.set_packrat(true);
+
+    assert!(parser.is_packrat());
+    assert!(parser.chunk());
+    assert_eq!(parser.queue(), &queue);
+}
@@ -0,0 +1,58 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "mmap")]
+
+#[macro_use]
+extern crate pest;
+
+use std::env;
+use std::fs;
+
+use pest::prelude::*;
+use pest::MappedFile;
+
+impl_rdp! {
+    grammar! {
+        entries = { entry* ~ eoi }
+        entry   = { key ~ ["="] ~ value ~ ["\n"] }
+        key     = { ['a'..'z']+ }
+        value   = { (!["\n"] ~ any)* }
+    }
+
+    process! {
+        values(&self) -> Vec<(&'input str, &'input str)> {
+            (_: entry, &key: key, &value: value, mut tail: values()) => {
+                tail.insert(0, (key, value));
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+#[test]
+fn entries() {
+    let path = env::temp_dir().join("pest_mapped_entries.txt");
+
+    fs::write(&path, "name=pest\nlicense=MPL 2.0\n").unwrap();
+
+    let file = MappedFile::open(&path).unwrap();
+    let values = {
+        let mut parser = Rdp::new(file.input());
+
+        assert!(parser.entries());
+
+        parser.set_queue_index(1);
+        parser.values()
+    };
+
+    assert_eq!(values, vec![("name", "pest"), ("license", "MPL 2.0")]);
+
+    fs::remove_file(&path).unwrap();
+}
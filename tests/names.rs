@@ -0,0 +1,57 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![allow(non_snake_case)]
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+// rules named like the helpers generated by `impl_rdp!`
+impl_rdp! {
+    grammar! {
+        names = { recover ~ LETTER ~ hold ~ move_hold ~ unhold ~ discard ~ cut ~ cut_scope ~
+                  merge_failures ~ recurse ~ memoize ~ with_label ~ track_pos ~ unique_labels ~
+                  take_failures ~ eoi }
+
+        recover = { ["a"] }
+        LETTER  = { [unicode(LETTER)] }
+
+        hold      = { ["h"] }
+        move_hold = { ["m"] }
+        unhold    = { ["u"] }
+        discard   = { ["d"] }
+
+        cut            = { ["c"] }
+        cut_scope      = { ["s"] }
+        merge_failures = { ["f"] }
+
+        recurse = { ["r"] }
+        memoize = { ["o"] }
+
+        with_label    = { ["w"] }
+        track_pos     = { ["t"] }
+        unique_labels = { ["l"] }
+        take_failures = { ["k"] }
+    }
+}
+
+#[test]
+fn names() {
+    let mut parser = Rdp::new(StringInput::new("aλhmudcsfrowtlk"));
+
+    assert!(parser.names());
+    assert!(parser.end());
+    assert_eq!(parser.queue()[1], Token::new(Rule::recover, 0, 1));
+    assert_eq!(parser.queue()[2], Token::new(Rule::LETTER, 1, 3));
+    assert_eq!(parser.queue()[6], Token::new(Rule::discard, 6, 7));
+    assert_eq!(parser.queue()[9], Token::new(Rule::merge_failures, 9, 10));
+    assert_eq!(parser.queue()[10], Token::new(Rule::recurse, 10, 11));
+    assert_eq!(parser.queue()[11], Token::new(Rule::memoize, 11, 12));
+    assert_eq!(parser.queue()[15], Token::new(Rule::take_failures, 15, 16));
+}
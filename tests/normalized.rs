@@ -0,0 +1,67 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+use pest::Normalization;
+
+impl_rdp! {
+    slice: SharedStr;
+
+    grammar! {
+        file   = { define* ~ eoi }
+        define = { ["#define"] ~ name ~ body ~ ["\n"] }
+        name   = @{ ['A'..'Z']+ }
+        body   = @{ (!["\n"] ~ any)* }
+
+        whitespace = _{ [" "] }
+    }
+}
+
+const SOURCE: &str = "\u{feff}#define A 1 + \\\r\n  2\r\n#define B\r\n#define c\r\n";
+
+fn normalized() -> NormalizedInput<'static> {
+    NormalizedInput::with_normalization(SOURCE, Normalization {
+        splices: true,
+        ..Normalization::default()
+    })
+}
+
+#[test]
+fn tokens() {
+    let mut parser = Rdp::new(normalized());
+
+    assert!(!parser.file());
+
+    parser.reset();
+
+    assert!(parser.define());
+
+    let body = parser.queue()[2];
+
+    assert_eq!(parser.input().slice(body.start, body.end), "1 +   2");
+
+    let (start, end) = parser.input().original_span(body.start, body.end);
+
+    assert_eq!(&SOURCE[start..end], "1 + \\\r\n  2");
+}
+
+#[test]
+fn errors() {
+    let mut parser = Rdp::new(normalized());
+
+    assert!(!parser.file());
+
+    let (expected, pos) = parser.expected();
+
+    assert_eq!(expected, vec![Rule::name]);
+    assert_eq!(&parser.input().as_str()[pos..pos + 1], "c");
+    assert_eq!(&SOURCE[parser.input().original_pos(pos)..][..1], "c");
+    assert_eq!(parser.input().line_col(pos), (4, 9));
+}
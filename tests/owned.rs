@@ -0,0 +1,86 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use std::thread;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    slice: SharedStr;
+
+    grammar! {
+        list  = { word ~ ([","] ~ word)* ~ eoi }
+        word  = @{ ['a'..'z']+ }
+
+        whitespace = _{ [" "] }
+    }
+
+    process! {
+        words(&self) -> Vec<SharedStr> {
+            (&word: word, mut tail: words()) => {
+                tail.insert(0, word);
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+struct Document {
+    parser: Rdp<OwnedInput>
+}
+
+impl Document {
+    fn load() -> Document {
+        let source = String::from("alpha, beta ,gamma");
+
+        Document {
+            parser: Rdp::new(OwnedInput::new(source))
+        }
+    }
+}
+
+#[test]
+fn outlives_source() {
+    let mut document = Document::load();
+
+    assert!(document.parser.list());
+    assert!(document.parser.end());
+
+    document.parser.set_queue_index(1);
+
+    assert_eq!(document.parser.words(), vec!["alpha", "beta", "gamma"]);
+}
+
+#[test]
+fn across_threads() {
+    let document = Document::load();
+
+    let words = thread::spawn(move || {
+        let mut parser = document.parser;
+
+        assert!(parser.list());
+
+        parser.set_queue_index(1);
+        parser.words()
+    }).join().unwrap();
+
+    assert_eq!(words[1], "beta");
+    assert_eq!((words[1].start(), words[1].end()), (7, 11));
+}
+
+#[test]
+fn expected() {
+    let mut parser = Rdp::new(OwnedInput::new("alpha,"));
+
+    assert!(!parser.list());
+    assert_eq!(parser.expected(), (vec![Rule::word], 6));
+}
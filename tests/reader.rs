@@ -0,0 +1,97 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use std::io::Cursor;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    slice: String;
+
+    grammar! {
+        record = { key ~ ["="] ~ value ~ ["\n"] | key ~ ["\n"] }
+        key    = { ['a'..'z']+ }
+        value  = { (!["\n"] ~ any)* }
+    }
+
+    process! {
+        pair(&self) -> (String, Option<String>) {
+            (_: record, &key: key, &value: value) => (key, Some(value)),
+            (_: record, &key: key) => (key, None)
+        }
+    }
+}
+
+#[test]
+fn records() {
+    let mut parser = Rdp::new(ReaderInput::new(Cursor::new("a=1\nbc\nd=ab cd\n")));
+    let mut pairs = vec![];
+
+    while parser.record() {
+        pairs.push(parser.pair());
+
+        parser.queue_mut().clear();
+        parser.set_queue_index(0);
+    }
+
+    assert!(parser.end());
+    assert_eq!(parser.input().discarded(), 15);
+    assert_eq!(parser.input().line_col(15), (4, 1));
+    assert_eq!(pairs, vec![
+        ("a".to_owned(), Some("1".to_owned())),
+        ("bc".to_owned(), None),
+        ("d".to_owned(), Some("ab cd".to_owned()))
+    ]);
+}
+
+#[test]
+fn expected() {
+    let mut parser = Rdp::new(ReaderInput::new(Cursor::new("ab=1\nc")));
+
+    assert!(parser.record());
+
+    parser.queue_mut().clear();
+
+    assert!(!parser.record());
+    assert_eq!(parser.expected(), (vec![Rule::record], 5));
+    assert_eq!(parser.input().discarded(), 5);
+}
+
+#[test]
+fn keep_queued() {
+    let mut parser = Rdp::new(ReaderInput::new(Cursor::new("a=1
+bc
+")));
+
+    assert!(parser.record());
+    assert!(parser.record());
+    assert!(parser.end());
+    assert_eq!(parser.input().discarded(), 0);
+    assert_eq!(parser.queue_with_captures()[0].1, "a=1\n");
+    assert!(parser.input_mut().take_error().is_none());
+}
+
+#[test]
+fn rewind_discarded() {
+    let mut parser = Rdp::new(ReaderInput::new(Cursor::new("a=1
+bc
+")));
+
+    assert!(parser.record());
+
+    parser.queue_mut().clear();
+
+    assert!(parser.record());
+
+    parser.reset();
+
+    assert!(!parser.record());
+    assert!(parser.input_mut().take_error().is_some());
+}
@@ -0,0 +1,108 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Ident,
+    Number,
+    Equals,
+    Newline
+}
+
+#[derive(Debug, PartialEq)]
+struct Lexed<'a> {
+    kind: Kind,
+    text: &'a str
+}
+
+impl<'a> Lexeme for Lexed<'a> {
+    type Kind = Kind;
+
+    fn kind(&self) -> Kind {
+        self.kind
+    }
+}
+
+fn lex(source: &str) -> Vec<Lexed<'_>> {
+    let mut lexed = vec![];
+
+    for line in source.lines() {
+        for text in line.split_whitespace() {
+            let kind = match text {
+                "=" => Kind::Equals,
+                _ if text.chars().all(|c| c.is_ascii_digit()) => Kind::Number,
+                _ => Kind::Ident
+            };
+
+            lexed.push(Lexed { kind, text });
+        }
+
+        lexed.push(Lexed { kind: Kind::Newline, text: "\n" });
+    }
+
+    lexed
+}
+
+impl_rdp! {
+    slice: &'input [Lexed<'input>];
+    kind: Kind;
+
+    grammar! {
+        program    = { assignment* ~ eoi }
+        assignment = { [kind(Kind::Ident)] ~ [kind(Kind::Equals)] ~ value ~ [kind(Kind::Newline)] }
+        value      = { [kind(Kind::Number)] | [kind(Kind::Ident)] }
+    }
+
+    process! {
+        assignments(&self) -> Vec<(&'input str, &'input str)> {
+            (&assignment: assignment, &value: value, mut tail: assignments()) => {
+                tail.insert(0, (assignment[0].text, value[0].text));
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+#[test]
+fn lexed() {
+    let lexed = lex("a = 1\nb = a\n");
+    let mut parser = Rdp::new(SliceInput::new(&lexed));
+
+    assert!(parser.program());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::program, 0, 8),
+        Token::new(Rule::assignment, 0, 4),
+        Token::new(Rule::value, 2, 3),
+        Token::new(Rule::assignment, 4, 8),
+        Token::new(Rule::value, 6, 7)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+
+    parser.set_queue_index(1);
+
+    assert_eq!(parser.assignments(), vec![("a", "1"), ("b", "a")]);
+}
+
+#[test]
+fn expected() {
+    let lexed = lex("a = 1\nb = =\n");
+    let mut parser = Rdp::new(SliceInput::new(&lexed));
+
+    assert!(!parser.program());
+    assert_eq!(parser.expected(), (vec![Rule::value], 6));
+    assert_eq!(lexed[6].text, "=");
+}
@@ -0,0 +1,83 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    grammar! {
+        words     = { word+ ~ eoi }
+        word      = @{ (!([" "] | eoi) ~ any)+ }
+        non_ascii = { ['\u{80}'..'\u{10ffff}']+ }
+        keyword   = { [i"σίσυφος"] | [i"straße"] | [i"kelvin"] }
+
+        whitespace = _{ [" "] }
+    }
+
+    process! {
+        all(&self) -> Vec<&'input str> {
+            (&head: word, mut tail: all()) => {
+                tail.insert(0, head);
+
+                tail
+            },
+            () => Vec::new()
+        }
+    }
+}
+
+#[test]
+fn any_mixed_script() {
+    let mut parser = Rdp::new(StringInput::new("héllo мир 世界 🎉"));
+
+    assert!(parser.words());
+    assert!(parser.end());
+
+    parser.inc_queue_index();
+
+    assert_eq!(parser.all(), vec!["héllo", "мир", "世界", "🎉"]);
+}
+
+#[test]
+fn range_across_lengths() {
+    let mut parser = Rdp::new(StringInput::new("é世🎉a"));
+
+    assert!(parser.non_ascii());
+    assert!(!parser.end());
+    assert_eq!(parser.queue(), &vec![Token::new(Rule::non_ascii, 0, 9)]);
+}
+
+#[test]
+fn insensitive_greek() {
+    let mut parser = Rdp::new(StringInput::new("ΣΊΣΥΦΟΣ"));
+
+    assert!(parser.keyword());
+    assert!(parser.end());
+}
+
+#[test]
+fn insensitive_sharp_s() {
+    let mut parser = Rdp::new(StringInput::new("STRAẞE"));
+
+    assert!(parser.keyword());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("STRASSE"));
+
+    assert!(!parser.keyword());
+}
+
+#[test]
+fn insensitive_kelvin_sign() {
+    let mut parser = Rdp::new(StringInput::new("\u{212a}ELVIN"));
+
+    assert!(parser.keyword());
+    assert!(parser.end());
+    assert_eq!(parser.queue(), &vec![Token::new(Rule::keyword, 0, 8)]);
+}
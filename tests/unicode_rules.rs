@@ -0,0 +1,66 @@
+// pest. Elegant, efficient grammars
+// Copyright (C) 2016  Dragoș Tiselice
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#[macro_use]
+extern crate pest;
+
+use pest::prelude::*;
+
+impl_rdp! {
+    grammar! {
+        statement = { ident ~ ["="] ~ number }
+        ident     = @{ ([unicode(XID_START)] | ["_"]) ~ [unicode(XID_CONTINUE)]* }
+        number    = @{ [unicode(DECIMAL_NUMBER)]+ }
+        word      = @{ [unicode(LETTER)] ~ ([unicode(LETTER)] | [unicode(MARK)])* }
+        title     = @{ [unicode(UPPERCASE_LETTER)] ~ [unicode(LOWERCASE_LETTER)]* }
+
+        whitespace = _{ [unicode(WHITE_SPACE)] }
+    }
+}
+
+#[test]
+fn identifiers() {
+    let mut parser = Rdp::new(StringInput::new("переменная_2\u{3000}=\u{a0}٤٢"));
+
+    assert!(parser.statement());
+    assert!(parser.end());
+
+    let queue = vec![
+        Token::new(Rule::statement, 0, 32),
+        Token::new(Rule::ident, 0, 22),
+        Token::new(Rule::number, 28, 32)
+    ];
+
+    assert_eq!(parser.queue(), &queue);
+}
+
+#[test]
+fn combining_marks() {
+    let mut parser = Rdp::new(StringInput::new("e\u{301}te\u{301}"));
+
+    assert!(parser.word());
+    assert!(parser.end());
+}
+
+#[test]
+fn expected() {
+    let mut parser = Rdp::new(StringInput::new("1abc"));
+
+    assert!(!parser.ident());
+    assert_eq!(parser.expected(), (vec![Rule::ident], 0));
+
+    let mut parser = Rdp::new(StringInput::new("Ωmega"));
+
+    assert!(parser.title());
+    assert!(parser.end());
+
+    let mut parser = Rdp::new(StringInput::new("x = a"));
+
+    assert!(!parser.statement());
+    assert_eq!(parser.expected(), (vec![Rule::number], 4));
+    assert_eq!(parser.expected_labels(), (vec!["WHITE_SPACE"], 4));
+}